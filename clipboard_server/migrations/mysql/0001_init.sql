@@ -1,5 +1,7 @@
+-- MySQL 初始表结构（兼容手工导入过旧版 tables.sql 的数据库）
+
 -- 用户表
-CREATE TABLE `clip_users` (
+CREATE TABLE IF NOT EXISTS `clip_users` (
                               `id` bigint(20) unsigned NOT NULL AUTO_INCREMENT COMMENT '用户ID',
                               `username` varchar(50) NOT NULL COMMENT '用户名',
                               `email` varchar(100) NOT NULL COMMENT '邮箱',
@@ -20,7 +22,7 @@ CREATE TABLE `clip_users` (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='用户表';
 
 -- 用户会话表（用于存储登录token）
-CREATE TABLE `clip_user_sessions` (
+CREATE TABLE IF NOT EXISTS `clip_user_sessions` (
                                       `id` bigint(20) unsigned NOT NULL AUTO_INCREMENT COMMENT '会话ID',
                                       `user_id` bigint(20) unsigned NOT NULL COMMENT '用户ID',
                                       `token` varchar(255) NOT NULL COMMENT '访问令牌',
@@ -40,7 +42,7 @@ CREATE TABLE `clip_user_sessions` (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='用户会话表';
 
 -- Clip内容表
-CREATE TABLE `clip_contents` (
+CREATE TABLE IF NOT EXISTS `clip_contents` (
                                  `id` bigint(20) unsigned NOT NULL AUTO_INCREMENT COMMENT '内容ID',
                                  `user_id` bigint(20) unsigned NOT NULL COMMENT '用户ID',
                                  `title` varchar(255) DEFAULT NULL COMMENT '标题',
@@ -64,12 +66,11 @@ CREATE TABLE `clip_contents` (
                                  KEY `idx_content_type` (`content_type`),
                                  KEY `idx_expires_at` (`expires_at`),
                                  KEY `idx_created_at` (`created_at`),
-                                 FULLTEXT KEY `ft_content` (`title`, `content`),
                                  CONSTRAINT `fk_content_user` FOREIGN KEY (`user_id`) REFERENCES `clip_users` (`id`) ON DELETE CASCADE
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Clip内容表';
 
 -- Clip访问日志表
-CREATE TABLE `clip_access_logs` (
+CREATE TABLE IF NOT EXISTS `clip_access_logs` (
                                     `id` bigint(20) unsigned NOT NULL AUTO_INCREMENT COMMENT '日志ID',
                                     `content_id` bigint(20) unsigned NOT NULL COMMENT '内容ID',
                                     `user_id` bigint(20) unsigned DEFAULT NULL COMMENT '访问用户ID（如果是登录用户）',
@@ -86,7 +87,7 @@ CREATE TABLE `clip_access_logs` (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Clip访问日志表';
 
 -- Clip标签表（可选，用于更好的标签管理）
-CREATE TABLE `clip_tags` (
+CREATE TABLE IF NOT EXISTS `clip_tags` (
                              `id` bigint(20) unsigned NOT NULL AUTO_INCREMENT COMMENT '标签ID',
                              `name` varchar(50) NOT NULL COMMENT '标签名称',
                              `user_id` bigint(20) unsigned NOT NULL COMMENT '用户ID',
@@ -96,4 +97,4 @@ CREATE TABLE `clip_tags` (
                              UNIQUE KEY `uk_user_tag` (`user_id`, `name`),
                              KEY `idx_name` (`name`),
                              CONSTRAINT `fk_tag_user` FOREIGN KEY (`user_id`) REFERENCES `clip_users` (`id`) ON DELETE CASCADE
-) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Clip标签表';
\ No newline at end of file
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Clip标签表';
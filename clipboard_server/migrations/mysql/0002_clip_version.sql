@@ -0,0 +1,2 @@
+-- Clip 版本号：每次更新加一，用于 ETag / If-Match 乐观并发控制
+ALTER TABLE `clip_contents` ADD COLUMN `version` int(11) NOT NULL DEFAULT '1' COMMENT '版本号' AFTER `view_count`;
@@ -0,0 +1,21 @@
+-- Clip 历史版本：每个版本保存一份标题、内容、标签快照，保留数量由用户设置
+ALTER TABLE `clip_users` ADD COLUMN `revision_retention` int(11) NOT NULL DEFAULT '20' COMMENT '保留的历史版本数' AFTER `login_count`;
+
+CREATE TABLE IF NOT EXISTS `clip_revisions` (
+                                  `id` bigint(20) unsigned NOT NULL AUTO_INCREMENT COMMENT '版本记录ID',
+                                  `clip_id` bigint(20) unsigned NOT NULL COMMENT '内容ID',
+                                  `user_id` bigint(20) unsigned NOT NULL COMMENT '用户ID',
+                                  `version` int(11) NOT NULL COMMENT '版本号',
+                                  `title` varchar(255) DEFAULT NULL COMMENT '标题',
+                                  `content` text NOT NULL COMMENT '内容',
+                                  `tags` json DEFAULT NULL COMMENT '标签数组',
+                                  `created_at` datetime NOT NULL COMMENT '该版本的保存时间',
+                                  PRIMARY KEY (`id`),
+                                  UNIQUE KEY `uk_clip_version` (`clip_id`, `version`),
+                                  KEY `idx_user_id` (`user_id`),
+                                  CONSTRAINT `fk_revision_content` FOREIGN KEY (`clip_id`) REFERENCES `clip_contents` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Clip历史版本表';
+
+-- 已有的 Clips 以当前内容作为第一条历史版本
+INSERT INTO `clip_revisions` (`clip_id`, `user_id`, `version`, `title`, `content`, `tags`, `created_at`)
+SELECT `id`, `user_id`, `version`, `title`, `content`, `tags`, `updated_at` FROM `clip_contents`;
@@ -0,0 +1,7 @@
+-- 定时任务锁：多实例部署时，同一任务在一个执行周期内只由一个实例执行
+CREATE TABLE IF NOT EXISTS `clip_job_locks` (
+                                  `name` varchar(64) NOT NULL COMMENT '任务名',
+                                  `owner` varchar(64) NOT NULL COMMENT '持有锁的实例ID',
+                                  `locked_until` datetime NOT NULL COMMENT '锁到期时间',
+                                  PRIMARY KEY (`name`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='定时任务锁表';
@@ -0,0 +1,2 @@
+-- 用户默认的 Clip 有效期（如 1d），为空表示永久有效
+ALTER TABLE `clip_users` ADD COLUMN `default_expires_in` varchar(16) DEFAULT NULL COMMENT '新建Clip的默认有效期' AFTER `revision_retention`;
@@ -0,0 +1,6 @@
+-- 刷新令牌族：同一次登录轮换出的刷新令牌共用一个族ID，旧令牌被重复使用时整族吊销
+ALTER TABLE `clip_user_sessions` ADD COLUMN `family_id` varchar(36) NOT NULL DEFAULT '' COMMENT '令牌族ID' AFTER `refresh_token`;
+ALTER TABLE `clip_user_sessions` ADD KEY `idx_family_id` (`family_id`);
+-- 令牌增加 typ、jti、fam 声明后长度超过 255
+ALTER TABLE `clip_user_sessions` MODIFY COLUMN `token` varchar(512) NOT NULL COMMENT '访问令牌';
+ALTER TABLE `clip_user_sessions` MODIFY COLUMN `refresh_token` varchar(512) NOT NULL COMMENT '刷新令牌';
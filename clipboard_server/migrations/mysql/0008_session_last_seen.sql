@@ -0,0 +1,2 @@
+-- 会话最近活跃时间，由认证中间件按间隔更新
+ALTER TABLE `clip_user_sessions` ADD COLUMN `last_seen_at` datetime DEFAULT NULL COMMENT '最近活跃时间' AFTER `ip_address`;
@@ -0,0 +1,15 @@
+-- 安全审计日志：记录修改密码等账号安全相关操作
+CREATE TABLE IF NOT EXISTS `clip_audit_logs` (
+                                  `id` bigint(20) unsigned NOT NULL AUTO_INCREMENT COMMENT '日志ID',
+                                  `user_id` bigint(20) unsigned DEFAULT NULL COMMENT '用户ID',
+                                  `event` varchar(50) NOT NULL COMMENT '事件类型',
+                                  `ip_address` varchar(45) DEFAULT NULL COMMENT 'IP地址',
+                                  `user_agent` varchar(500) DEFAULT NULL COMMENT '用户代理',
+                                  `detail` json DEFAULT NULL COMMENT '事件详情',
+                                  `created_at` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP COMMENT '发生时间',
+                                  PRIMARY KEY (`id`),
+                                  KEY `idx_user_id` (`user_id`),
+                                  KEY `idx_event` (`event`),
+                                  KEY `idx_created_at` (`created_at`),
+                                  CONSTRAINT `fk_audit_user` FOREIGN KEY (`user_id`) REFERENCES `clip_users` (`id`) ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='安全审计日志表';
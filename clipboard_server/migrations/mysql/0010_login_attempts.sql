@@ -0,0 +1,9 @@
+-- 登录失败计数：按账号（user:用户名）和 IP（ip:地址）分别计数，达到上限后锁定
+CREATE TABLE IF NOT EXISTS `clip_login_attempts` (
+                                  `attempt_key` varchar(191) NOT NULL COMMENT '计数键',
+                                  `failures` int(11) NOT NULL DEFAULT 0 COMMENT '连续失败次数',
+                                  `last_failed_at` datetime NOT NULL COMMENT '最近失败时间',
+                                  `locked_until` datetime DEFAULT NULL COMMENT '锁定到期时间',
+                                  PRIMARY KEY (`attempt_key`),
+                                  KEY `idx_last_failed_at` (`last_failed_at`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='登录失败计数表';
@@ -0,0 +1,10 @@
+-- 彻底删除的 Clip 留下墓碑，增量同步据此通知离线设备删除本地副本
+CREATE TABLE IF NOT EXISTS `clip_tombstones` (
+                                   `clip_id` bigint(20) unsigned NOT NULL COMMENT '已删除的 Clip ID',
+                                   `user_id` bigint(20) unsigned NOT NULL COMMENT '用户ID',
+                                   `deleted_at` datetime NOT NULL COMMENT '彻底删除时间',
+                                   PRIMARY KEY (`clip_id`),
+                                   KEY `idx_user_deleted_at` (`user_id`, `deleted_at`),
+                                   KEY `idx_deleted_at` (`deleted_at`),
+                                   CONSTRAINT `fk_tombstone_user` FOREIGN KEY (`user_id`) REFERENCES `clip_users` (`id`) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Clip 删除墓碑表';
@@ -0,0 +1,122 @@
+-- PostgreSQL 表结构（与 migrations/mysql 的 MySQL 表结构保持一致）
+-- MySQL 专有语法的对应关系：
+--   AUTO_INCREMENT / last_insert_id()  -> BIGSERIAL + INSERT ... RETURNING id
+--   json                               -> jsonb
+--   ON UPDATE CURRENT_TIMESTAMP        -> BEFORE UPDATE 触发器 set_updated_at()
+
+-- 自动维护 updated_at
+CREATE OR REPLACE FUNCTION set_updated_at() RETURNS trigger AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- 用户表
+CREATE TABLE IF NOT EXISTS clip_users (
+    id BIGSERIAL PRIMARY KEY,
+    username VARCHAR(50) NOT NULL,
+    email VARCHAR(100) NOT NULL,
+    password_hash VARCHAR(255) NOT NULL,
+    salt VARCHAR(50) NOT NULL,
+    status SMALLINT NOT NULL DEFAULT 1,
+    last_login_at TIMESTAMPTZ DEFAULT NULL,
+    last_login_ip VARCHAR(45) DEFAULT NULL,
+    login_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMPTZ DEFAULT NULL,
+    CONSTRAINT uk_username UNIQUE (username),
+    CONSTRAINT uk_email UNIQUE (email)
+);
+CREATE INDEX IF NOT EXISTS idx_users_status ON clip_users (status);
+CREATE INDEX IF NOT EXISTS idx_users_created_at ON clip_users (created_at);
+COMMENT ON TABLE clip_users IS '用户表';
+
+DROP TRIGGER IF EXISTS trg_clip_users_updated_at ON clip_users;
+CREATE TRIGGER trg_clip_users_updated_at
+    BEFORE UPDATE ON clip_users
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- 用户会话表（用于存储登录token）
+CREATE TABLE IF NOT EXISTS clip_user_sessions (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES clip_users (id) ON DELETE CASCADE,
+    token VARCHAR(255) NOT NULL,
+    refresh_token VARCHAR(255) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    refresh_expires_at TIMESTAMPTZ NOT NULL,
+    device_info VARCHAR(500) DEFAULT NULL,
+    ip_address VARCHAR(45) DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT uk_token UNIQUE (token),
+    CONSTRAINT uk_refresh_token UNIQUE (refresh_token)
+);
+CREATE INDEX IF NOT EXISTS idx_sessions_user_id ON clip_user_sessions (user_id);
+CREATE INDEX IF NOT EXISTS idx_sessions_expires_at ON clip_user_sessions (expires_at);
+COMMENT ON TABLE clip_user_sessions IS '用户会话表';
+
+DROP TRIGGER IF EXISTS trg_clip_user_sessions_updated_at ON clip_user_sessions;
+CREATE TRIGGER trg_clip_user_sessions_updated_at
+    BEFORE UPDATE ON clip_user_sessions
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- Clip内容表
+CREATE TABLE IF NOT EXISTS clip_contents (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES clip_users (id) ON DELETE CASCADE,
+    title VARCHAR(255) DEFAULT NULL,
+    content TEXT NOT NULL,
+    content_type VARCHAR(20) NOT NULL DEFAULT 'text',
+    language VARCHAR(50) DEFAULT NULL,
+    is_encrypted SMALLINT NOT NULL DEFAULT 0,
+    encryption_key VARCHAR(255) DEFAULT NULL,
+    access_type VARCHAR(20) NOT NULL DEFAULT 'private',
+    view_count INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ DEFAULT NULL,
+    short_url VARCHAR(50) DEFAULT NULL,
+    tags JSONB DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMPTZ DEFAULT NULL,
+    CONSTRAINT uk_short_url UNIQUE (short_url)
+);
+CREATE INDEX IF NOT EXISTS idx_contents_user_id ON clip_contents (user_id);
+CREATE INDEX IF NOT EXISTS idx_contents_access_type ON clip_contents (access_type);
+CREATE INDEX IF NOT EXISTS idx_contents_content_type ON clip_contents (content_type);
+CREATE INDEX IF NOT EXISTS idx_contents_expires_at ON clip_contents (expires_at);
+CREATE INDEX IF NOT EXISTS idx_contents_created_at ON clip_contents (created_at);
+COMMENT ON TABLE clip_contents IS 'Clip内容表';
+
+DROP TRIGGER IF EXISTS trg_clip_contents_updated_at ON clip_contents;
+CREATE TRIGGER trg_clip_contents_updated_at
+    BEFORE UPDATE ON clip_contents
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- Clip访问日志表
+CREATE TABLE IF NOT EXISTS clip_access_logs (
+    id BIGSERIAL PRIMARY KEY,
+    content_id BIGINT NOT NULL REFERENCES clip_contents (id) ON DELETE CASCADE,
+    user_id BIGINT DEFAULT NULL REFERENCES clip_users (id) ON DELETE SET NULL,
+    access_ip VARCHAR(45) NOT NULL,
+    user_agent VARCHAR(500) DEFAULT NULL,
+    referrer VARCHAR(500) DEFAULT NULL,
+    accessed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_logs_content_id ON clip_access_logs (content_id);
+CREATE INDEX IF NOT EXISTS idx_logs_user_id ON clip_access_logs (user_id);
+CREATE INDEX IF NOT EXISTS idx_logs_accessed_at ON clip_access_logs (accessed_at);
+COMMENT ON TABLE clip_access_logs IS 'Clip访问日志表';
+
+-- Clip标签表（可选，用于更好的标签管理）
+CREATE TABLE IF NOT EXISTS clip_tags (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(50) NOT NULL,
+    user_id BIGINT NOT NULL REFERENCES clip_users (id) ON DELETE CASCADE,
+    usage_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT uk_user_tag UNIQUE (user_id, name)
+);
+CREATE INDEX IF NOT EXISTS idx_tags_name ON clip_tags (name);
+COMMENT ON TABLE clip_tags IS 'Clip标签表';
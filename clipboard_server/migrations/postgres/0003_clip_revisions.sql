@@ -0,0 +1,21 @@
+-- Clip 历史版本：每个版本保存一份标题、内容、标签快照，保留数量由用户设置
+ALTER TABLE clip_users ADD COLUMN IF NOT EXISTS revision_retention INTEGER NOT NULL DEFAULT 20;
+
+CREATE TABLE IF NOT EXISTS clip_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    clip_id BIGINT NOT NULL REFERENCES clip_contents (id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    version INTEGER NOT NULL,
+    title VARCHAR(255) DEFAULT NULL,
+    content TEXT NOT NULL,
+    tags JSONB DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    CONSTRAINT uk_clip_version UNIQUE (clip_id, version)
+);
+CREATE INDEX IF NOT EXISTS idx_revisions_user_id ON clip_revisions (user_id);
+COMMENT ON TABLE clip_revisions IS 'Clip历史版本表';
+
+-- 已有的 Clips 以当前内容作为第一条历史版本
+INSERT INTO clip_revisions (clip_id, user_id, version, title, content, tags, created_at)
+SELECT id, user_id, version, title, content, tags, updated_at FROM clip_contents
+ON CONFLICT DO NOTHING;
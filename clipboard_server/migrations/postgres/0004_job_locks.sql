@@ -0,0 +1,7 @@
+-- 定时任务锁：多实例部署时，同一任务在一个执行周期内只由一个实例执行
+CREATE TABLE IF NOT EXISTS clip_job_locks (
+    name VARCHAR(64) PRIMARY KEY,
+    owner VARCHAR(64) NOT NULL,
+    locked_until TIMESTAMPTZ NOT NULL
+);
+COMMENT ON TABLE clip_job_locks IS '定时任务锁表';
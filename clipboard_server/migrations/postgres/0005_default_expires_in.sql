@@ -0,0 +1,2 @@
+-- 用户默认的 Clip 有效期（如 1d），为空表示永久有效
+ALTER TABLE clip_users ADD COLUMN IF NOT EXISTS default_expires_in VARCHAR(16) DEFAULT NULL;
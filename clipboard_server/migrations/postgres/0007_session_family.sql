@@ -0,0 +1,6 @@
+-- 刷新令牌族：同一次登录轮换出的刷新令牌共用一个族ID，旧令牌被重复使用时整族吊销
+ALTER TABLE clip_user_sessions ADD COLUMN IF NOT EXISTS family_id VARCHAR(36) NOT NULL DEFAULT '';
+CREATE INDEX IF NOT EXISTS idx_sessions_family_id ON clip_user_sessions (family_id);
+-- 令牌增加 typ、jti、fam 声明后长度超过 255
+ALTER TABLE clip_user_sessions ALTER COLUMN token TYPE VARCHAR(512);
+ALTER TABLE clip_user_sessions ALTER COLUMN refresh_token TYPE VARCHAR(512);
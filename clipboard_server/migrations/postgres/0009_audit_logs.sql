@@ -0,0 +1,14 @@
+-- 安全审计日志：记录修改密码等账号安全相关操作
+CREATE TABLE IF NOT EXISTS clip_audit_logs (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT DEFAULT NULL REFERENCES clip_users (id) ON DELETE SET NULL,
+    event VARCHAR(50) NOT NULL,
+    ip_address VARCHAR(45) DEFAULT NULL,
+    user_agent VARCHAR(500) DEFAULT NULL,
+    detail JSONB DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_user_id ON clip_audit_logs (user_id);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_event ON clip_audit_logs (event);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_created_at ON clip_audit_logs (created_at);
+COMMENT ON TABLE clip_audit_logs IS '安全审计日志表';
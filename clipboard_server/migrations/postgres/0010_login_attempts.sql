@@ -0,0 +1,9 @@
+-- 登录失败计数：按账号（user:用户名）和 IP（ip:地址）分别计数，达到上限后锁定
+CREATE TABLE IF NOT EXISTS clip_login_attempts (
+    attempt_key VARCHAR(191) PRIMARY KEY,
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMPTZ NOT NULL,
+    locked_until TIMESTAMPTZ DEFAULT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_login_attempts_last_failed_at ON clip_login_attempts (last_failed_at);
+COMMENT ON TABLE clip_login_attempts IS '登录失败计数表';
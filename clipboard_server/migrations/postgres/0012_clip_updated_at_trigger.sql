@@ -0,0 +1,8 @@
+-- 只在内容相关的列被更新时刷新 updated_at：查看计数（view_count）不算修改，
+-- 否则每次分享访问都会让 Clip 重新出现在增量同步中，并改变按更新时间的排序
+DROP TRIGGER IF EXISTS trg_clip_contents_updated_at ON clip_contents;
+CREATE TRIGGER trg_clip_contents_updated_at
+    BEFORE UPDATE OF user_id, title, content, content_type, language, is_encrypted, encryption_key,
+                     access_type, expires_at, max_views, short_url, tags, version, deleted_at
+    ON clip_contents
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
@@ -0,0 +1,86 @@
+-- SQLite 表结构（与 migrations/mysql 的 MySQL 表结构保持一致）
+-- 时间字段统一由服务端写入 RFC 3339 格式的 UTC 时间
+
+-- 用户表
+CREATE TABLE IF NOT EXISTS clip_users (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    username TEXT NOT NULL UNIQUE,
+    email TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    salt TEXT NOT NULL,
+    status INTEGER NOT NULL DEFAULT 1,
+    last_login_at TEXT DEFAULT NULL,
+    last_login_ip TEXT DEFAULT NULL,
+    login_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    deleted_at TEXT DEFAULT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_users_status ON clip_users (status);
+CREATE INDEX IF NOT EXISTS idx_users_created_at ON clip_users (created_at);
+
+-- 用户会话表（用于存储登录token）
+CREATE TABLE IF NOT EXISTS clip_user_sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES clip_users (id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE,
+    refresh_token TEXT NOT NULL UNIQUE,
+    expires_at TEXT NOT NULL,
+    refresh_expires_at TEXT NOT NULL,
+    device_info TEXT DEFAULT NULL,
+    ip_address TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_sessions_user_id ON clip_user_sessions (user_id);
+CREATE INDEX IF NOT EXISTS idx_sessions_expires_at ON clip_user_sessions (expires_at);
+
+-- Clip内容表
+CREATE TABLE IF NOT EXISTS clip_contents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES clip_users (id) ON DELETE CASCADE,
+    title TEXT DEFAULT NULL,
+    content TEXT NOT NULL,
+    content_type TEXT NOT NULL DEFAULT 'text',
+    language TEXT DEFAULT NULL,
+    is_encrypted INTEGER NOT NULL DEFAULT 0,
+    encryption_key TEXT DEFAULT NULL,
+    access_type TEXT NOT NULL DEFAULT 'private',
+    view_count INTEGER NOT NULL DEFAULT 0,
+    expires_at TEXT DEFAULT NULL,
+    short_url TEXT DEFAULT NULL UNIQUE,
+    tags TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    deleted_at TEXT DEFAULT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_contents_user_id ON clip_contents (user_id);
+CREATE INDEX IF NOT EXISTS idx_contents_access_type ON clip_contents (access_type);
+CREATE INDEX IF NOT EXISTS idx_contents_content_type ON clip_contents (content_type);
+CREATE INDEX IF NOT EXISTS idx_contents_expires_at ON clip_contents (expires_at);
+CREATE INDEX IF NOT EXISTS idx_contents_created_at ON clip_contents (created_at);
+
+-- Clip访问日志表
+CREATE TABLE IF NOT EXISTS clip_access_logs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content_id INTEGER NOT NULL REFERENCES clip_contents (id) ON DELETE CASCADE,
+    user_id INTEGER DEFAULT NULL REFERENCES clip_users (id) ON DELETE SET NULL,
+    access_ip TEXT NOT NULL,
+    user_agent TEXT DEFAULT NULL,
+    referrer TEXT DEFAULT NULL,
+    accessed_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_logs_content_id ON clip_access_logs (content_id);
+CREATE INDEX IF NOT EXISTS idx_logs_user_id ON clip_access_logs (user_id);
+CREATE INDEX IF NOT EXISTS idx_logs_accessed_at ON clip_access_logs (accessed_at);
+
+-- Clip标签表（可选，用于更好的标签管理）
+CREATE TABLE IF NOT EXISTS clip_tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    user_id INTEGER NOT NULL REFERENCES clip_users (id) ON DELETE CASCADE,
+    usage_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    UNIQUE (user_id, name)
+);
+CREATE INDEX IF NOT EXISTS idx_tags_name ON clip_tags (name);
@@ -0,0 +1,6 @@
+-- 定时任务锁：多实例部署时，同一任务在一个执行周期内只由一个实例执行
+CREATE TABLE IF NOT EXISTS clip_job_locks (
+    name TEXT PRIMARY KEY,
+    owner TEXT NOT NULL,
+    locked_until TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- 查看次数上限：达到上限后 Clip 被彻底删除（阅后即焚为上限 1）
+ALTER TABLE clip_contents ADD COLUMN max_views INTEGER DEFAULT NULL;
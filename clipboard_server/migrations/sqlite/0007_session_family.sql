@@ -0,0 +1,3 @@
+-- 刷新令牌族：同一次登录轮换出的刷新令牌共用一个族ID，旧令牌被重复使用时整族吊销
+ALTER TABLE clip_user_sessions ADD COLUMN family_id TEXT NOT NULL DEFAULT '';
+CREATE INDEX IF NOT EXISTS idx_sessions_family_id ON clip_user_sessions (family_id);
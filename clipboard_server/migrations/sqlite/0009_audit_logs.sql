@@ -0,0 +1,13 @@
+-- 安全审计日志：记录修改密码等账号安全相关操作
+CREATE TABLE IF NOT EXISTS clip_audit_logs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER DEFAULT NULL REFERENCES clip_users (id) ON DELETE SET NULL,
+    event TEXT NOT NULL,
+    ip_address TEXT DEFAULT NULL,
+    user_agent TEXT DEFAULT NULL,
+    detail TEXT DEFAULT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_user_id ON clip_audit_logs (user_id);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_event ON clip_audit_logs (event);
+CREATE INDEX IF NOT EXISTS idx_audit_logs_created_at ON clip_audit_logs (created_at);
@@ -0,0 +1,8 @@
+-- 登录失败计数：按账号（user:用户名）和 IP（ip:地址）分别计数，达到上限后锁定
+CREATE TABLE IF NOT EXISTS clip_login_attempts (
+    attempt_key TEXT PRIMARY KEY,
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_failed_at TEXT NOT NULL,
+    locked_until TEXT DEFAULT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_login_attempts_last_failed_at ON clip_login_attempts (last_failed_at);
@@ -0,0 +1,8 @@
+-- 彻底删除的 Clip 留下墓碑，增量同步据此通知离线设备删除本地副本
+CREATE TABLE IF NOT EXISTS clip_tombstones (
+    clip_id INTEGER PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES clip_users (id) ON DELETE CASCADE,
+    deleted_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_tombstones_user_deleted_at ON clip_tombstones (user_id, deleted_at);
+CREATE INDEX IF NOT EXISTS idx_tombstones_deleted_at ON clip_tombstones (deleted_at);
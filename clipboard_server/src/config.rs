@@ -1,12 +1,117 @@
-use std::env;
+use std::{env, net::IpAddr};
+
+/// 数据库后端类型（由 DATABASE_URL 的 scheme 决定）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseBackend {
+    MySql,
+    Postgres,
+    Sqlite,
+    Memory,
+}
+
+impl DatabaseBackend {
+    /// 根据连接串的 scheme 判断后端类型
+    pub fn from_url(url: &str) -> Option<Self> {
+        let scheme = url.split(':').next()?;
+
+        match scheme {
+            "mysql" | "mariadb" => Some(DatabaseBackend::MySql),
+            "postgres" | "postgresql" => Some(DatabaseBackend::Postgres),
+            "sqlite" => Some(DatabaseBackend::Sqlite),
+            "memory" => Some(DatabaseBackend::Memory),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DatabaseBackend::MySql => "MySQL",
+            DatabaseBackend::Postgres => "PostgreSQL",
+            DatabaseBackend::Sqlite => "SQLite",
+            DatabaseBackend::Memory => "Memory",
+        }
+    }
+}
+
+/// IP 地址段（CIDR），不带前缀长度时表示单个地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    /// 解析 `10.0.0.1`、`10.0.0.0/8`、`::1`、`fd00::/8` 等形式
+    pub fn parse(value: &str) -> Option<Self> {
+        let (addr, prefix) = match value.trim().split_once('/') {
+            Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, Some(prefix.parse::<u8>().ok()?)),
+            None => (value.trim().parse::<IpAddr>().ok()?, None),
+        };
+        let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max_prefix);
+
+        (prefix <= max_prefix).then_some(IpNetwork { addr, prefix })
+    }
+
+    /// ip 是否在该地址段内，IPv4 映射的 IPv6 地址按 IPv4 比较
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let shift = 32 - self.prefix as u32;
+                u32::from(network).checked_shr(shift).unwrap_or(0) == u32::from(ip).checked_shr(shift).unwrap_or(0)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let shift = 128 - self.prefix as u32;
+                u128::from(network).checked_shr(shift).unwrap_or(0) == u128::from(ip).checked_shr(shift).unwrap_or(0)
+            }
+            _ => false,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database_url: String,
+    pub database_backend: DatabaseBackend,
+    pub database_auto_migrate: bool,
     pub server_port: u16,
+    /// 对外访问的根地址，用于生成分享链接（不含末尾的 /）
+    pub public_base_url: String,
     pub jwt_secret: String,
     pub jwt_expires_in: i64,
     pub jwt_refresh_expires_in: i64,
+    /// 回收站中的 Clip 超过该天数后彻底删除
+    pub trash_retention_days: i64,
+    /// 公开（public / unlisted）Clip 的最长有效天数，0 表示不限制
+    pub public_max_ttl_days: i64,
+    /// 访问日志保留天数
+    pub access_log_retention_days: i64,
+    /// 删除墓碑保留天数；同步游标早于该期限时客户端需重新全量同步
+    pub sync_tombstone_retention_days: i64,
+    /// 管理员用户名（可访问 /api/admin 接口），与用户名一样不区分大小写
+    pub admin_users: Vec<String>,
+    /// 同一账号连续登录失败多少次后锁定
+    pub login_max_failures: i32,
+    /// 同一 IP 连续登录失败多少次后锁定
+    pub login_ip_max_failures: i32,
+    /// 首次锁定的秒数，之后每多失败一次翻倍
+    pub login_lockout_seconds: i64,
+    /// 匿名请求每个 IP 每分钟的请求数上限，0 表示不限制
+    pub rate_limit_anonymous_per_minute: u32,
+    /// 已登录请求每个用户每分钟的请求数上限，0 表示不限制
+    pub rate_limit_user_per_minute: u32,
+    /// 注册、登录、刷新令牌、修改密码接口每分钟的请求数上限，0 表示不限制
+    pub rate_limit_auth_per_minute: u32,
+    /// 新建、修改、删除 Clip 每分钟的请求数上限，0 表示不限制
+    pub rate_limit_write_per_minute: u32,
+    /// 访问分享链接每分钟的请求数上限，0 表示不限制
+    pub rate_limit_share_per_minute: u32,
+    /// 每个 IP 每分钟的请求数上限，在认证之前检查、不论是否登录，0 表示不限制
+    pub rate_limit_ip_per_minute: u32,
+    /// 限流器最多保留的令牌桶数量（每个主体至多占用三个）
+    pub rate_limit_max_entries: usize,
+    /// 受信任的反向代理，只有来自这些地址的请求才按 X-Forwarded-For / X-Real-IP 取客户端 IP
+    pub trusted_proxies: Vec<IpNetwork>,
 }
 
 impl Config {
@@ -16,11 +121,24 @@ impl Config {
         let database_url = env::var("DATABASE_URL")
             .expect("DATABASE_URL must be set in .env file");
 
+        let database_backend = DatabaseBackend::from_url(&database_url)
+            .expect("DATABASE_URL must start with mysql://, postgres://, sqlite: or memory://");
+
+        let database_auto_migrate = env::var("DATABASE_AUTO_MIGRATE")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()
+            .expect("DATABASE_AUTO_MIGRATE must be true or false");
+
         let server_port = env::var("SERVER_PORT")
             .unwrap_or_else(|_| "3000".to_string())
             .parse()
             .expect("SERVER_PORT must be a valid number");
 
+        let public_base_url = env::var("PUBLIC_BASE_URL")
+            .unwrap_or_else(|_| format!("http://localhost:{}", server_port))
+            .trim_end_matches('/')
+            .to_string();
+
         let jwt_secret = env::var("JWT_SECRET")
             .unwrap_or_else(|_| "default_secret_key".to_string());
 
@@ -34,12 +152,117 @@ impl Config {
             .parse()
             .expect("JWT_REFRESH_EXPIRES_IN must be a valid number");
 
+        let trash_retention_days = env::var("TRASH_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("TRASH_RETENTION_DAYS must be a valid number");
+
+        let public_max_ttl_days = env::var("PUBLIC_MAX_TTL_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("PUBLIC_MAX_TTL_DAYS must be a valid number");
+
+        let access_log_retention_days = env::var("ACCESS_LOG_RETENTION_DAYS")
+            .unwrap_or_else(|_| "90".to_string())
+            .parse()
+            .expect("ACCESS_LOG_RETENTION_DAYS must be a valid number");
+
+        let sync_tombstone_retention_days = env::var("SYNC_TOMBSTONE_RETENTION_DAYS")
+            .unwrap_or_else(|_| "90".to_string())
+            .parse()
+            .expect("SYNC_TOMBSTONE_RETENTION_DAYS must be a valid number");
+
+        let login_max_failures = env::var("LOGIN_MAX_FAILURES")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("LOGIN_MAX_FAILURES must be a valid number");
+
+        let login_ip_max_failures = env::var("LOGIN_IP_MAX_FAILURES")
+            .unwrap_or_else(|_| "20".to_string())
+            .parse()
+            .expect("LOGIN_IP_MAX_FAILURES must be a valid number");
+
+        let login_lockout_seconds = env::var("LOGIN_LOCKOUT_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .expect("LOGIN_LOCKOUT_SECONDS must be a valid number");
+
+        let rate_limit_anonymous_per_minute = env::var("RATE_LIMIT_ANONYMOUS_PER_MINUTE")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .expect("RATE_LIMIT_ANONYMOUS_PER_MINUTE must be a valid number");
+
+        let rate_limit_user_per_minute = env::var("RATE_LIMIT_USER_PER_MINUTE")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()
+            .expect("RATE_LIMIT_USER_PER_MINUTE must be a valid number");
+
+        let rate_limit_auth_per_minute = env::var("RATE_LIMIT_AUTH_PER_MINUTE")
+            .unwrap_or_else(|_| "20".to_string())
+            .parse()
+            .expect("RATE_LIMIT_AUTH_PER_MINUTE must be a valid number");
+
+        let rate_limit_write_per_minute = env::var("RATE_LIMIT_WRITE_PER_MINUTE")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .expect("RATE_LIMIT_WRITE_PER_MINUTE must be a valid number");
+
+        let rate_limit_share_per_minute = env::var("RATE_LIMIT_SHARE_PER_MINUTE")
+            .unwrap_or_else(|_| "120".to_string())
+            .parse()
+            .expect("RATE_LIMIT_SHARE_PER_MINUTE must be a valid number");
+
+        let rate_limit_ip_per_minute = env::var("RATE_LIMIT_IP_PER_MINUTE")
+            .unwrap_or_else(|_| "600".to_string())
+            .parse()
+            .expect("RATE_LIMIT_IP_PER_MINUTE must be a valid number");
+
+        let rate_limit_max_entries = env::var("RATE_LIMIT_MAX_ENTRIES")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .expect("RATE_LIMIT_MAX_ENTRIES must be a valid number");
+
+        // 逗号分隔的 IP 或 CIDR 列表，默认不信任任何代理
+        let trusted_proxies = env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| IpNetwork::parse(value).expect("TRUSTED_PROXIES must be a comma-separated list of IP addresses or CIDR ranges"))
+            .collect();
+
+        // 逗号分隔的用户名列表，默认没有管理员
+        let admin_users = env::var("ADMIN_USERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect();
+
         Config {
             database_url,
+            database_backend,
+            database_auto_migrate,
             server_port,
+            public_base_url,
             jwt_secret,
             jwt_expires_in,
             jwt_refresh_expires_in,
+            trash_retention_days,
+            public_max_ttl_days,
+            access_log_retention_days,
+            sync_tombstone_retention_days,
+            admin_users,
+            login_max_failures,
+            login_ip_max_failures,
+            login_lockout_seconds,
+            rate_limit_anonymous_per_minute,
+            rate_limit_user_per_minute,
+            rate_limit_auth_per_minute,
+            rate_limit_write_per_minute,
+            rate_limit_share_per_minute,
+            rate_limit_ip_per_minute,
+            rate_limit_max_entries,
+            trusted_proxies,
         }
     }
-}
\ No newline at end of file
+}
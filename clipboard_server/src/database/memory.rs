@@ -0,0 +1,786 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use super::{
+    generate_short_url, hash_password, tags_to_json,
+    models::*, AuditRepository, ClipRepository, JobRepository, LoginAttemptRepository, SessionRepository, Storage, UserRepository,
+};
+
+/// 内存存储后端（DATABASE_URL=memory://）
+///
+/// 用于演示服务和集成测试，进程退出后数据即丢失。语义与 SQL 后端保持一致：
+/// 软删除的记录对查询不可见。
+#[derive(Default)]
+pub struct MemoryStorage {
+    state: Mutex<MemoryState>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    users: Vec<Row<User>>,
+    sessions: Vec<UserSession>,
+    clips: Vec<Row<ClipContent>>,
+    revisions: Vec<ClipRevision>,
+    settings: HashMap<i64, UserSettings>,
+    /// 任务名 -> (持有者, 到期时间)
+    job_locks: HashMap<String, (String, DateTime<Utc>)>,
+    login_attempts: HashMap<String, LoginAttempt>,
+    /// (用户ID, 墓碑)
+    tombstones: Vec<(i64, ClipTombstone)>,
+    next_user_id: i64,
+    next_session_id: i64,
+    next_clip_id: i64,
+}
+
+/// 登录失败计数
+struct LoginAttempt {
+    failures: i32,
+    last_failed_at: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+/// 带软删除标记的记录
+struct Row<T> {
+    data: T,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+impl<T> Row<T> {
+    fn new(data: T) -> Self {
+        Self { data, deleted_at: None }
+    }
+
+    fn is_live(&self) -> bool {
+        self.deleted_at.is_none()
+    }
+}
+
+impl MemoryState {
+    /// 与表结构中 clip_users.revision_retention 的默认值一致
+    fn settings(&self, user_id: i64) -> UserSettings {
+        self.settings.get(&user_id)
+            .cloned()
+            .unwrap_or(UserSettings { revision_retention: 20, default_expires_in: None })
+    }
+
+    /// 彻底删除满足条件的 Clips 及其历史版本并写入墓碑，返回删除数量
+    fn purge_clips(&mut self, predicate: impl Fn(&Row<ClipContent>) -> bool) -> u64 {
+        let now = Utc::now();
+        let (purged, kept): (Vec<_>, Vec<_>) = self.clips.drain(..).partition(|c| predicate(c));
+        self.clips = kept;
+        let removed = purged.len() as u64;
+
+        for clip in purged {
+            self.tombstones.retain(|(_, t)| t.id != clip.data.id);
+            self.tombstones.push((clip.data.user_id, ClipTombstone { id: clip.data.id, deleted_at: now }));
+        }
+
+        if removed > 0 {
+            let clips = &self.clips;
+            self.revisions.retain(|r| clips.iter().any(|c| c.data.id == r.clip_id));
+        }
+        removed
+    }
+
+    /// 将 Clip 的当前内容写入历史版本，并清理超出保留数量的旧版本
+    fn save_revision(&mut self, clip: &ClipContent) {
+        self.revisions.push(ClipRevision {
+            clip_id: clip.id,
+            version: clip.version,
+            title: clip.title.clone(),
+            content: clip.content.clone(),
+            tags: clip.tags.clone(),
+            created_at: clip.updated_at,
+        });
+
+        let oldest = clip.version - self.settings(clip.user_id).revision_retention;
+        self.revisions.retain(|r| r.clip_id != clip.id || r.version >= oldest);
+    }
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MemoryState> {
+        // 持锁期间不会 panic，出现中毒时直接沿用内部数据
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 模拟唯一索引冲突
+fn duplicate_entry(value: &str, key: &str) -> sqlx::Error {
+    sqlx::Error::Protocol(format!("Duplicate entry '{}' for key '{}'", value, key))
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    fn users(&self) -> &dyn UserRepository {
+        self
+    }
+
+    fn sessions(&self) -> &dyn SessionRepository {
+        self
+    }
+
+    fn clips(&self) -> &dyn ClipRepository {
+        self
+    }
+
+    fn jobs(&self) -> &dyn JobRepository {
+        self
+    }
+
+    fn audit(&self) -> &dyn AuditRepository {
+        self
+    }
+
+    fn login_attempts(&self) -> &dyn LoginAttemptRepository {
+        self
+    }
+
+    async fn migrate(&self, _apply: bool) -> Result<i64, sqlx::Error> {
+        // 内存存储没有表结构
+        Ok(0)
+    }
+
+    async fn health_check(&self) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UserRepository for MemoryStorage {
+    async fn create_user(&self, user_data: &CreateUser) -> Result<User, sqlx::Error> {
+        let (password_hash, salt) = hash_password(&user_data.password)?;
+        let mut state = self.state();
+
+        // 与 SQL 后端的唯一索引一致：软删除的用户同样占用用户名和邮箱
+        if state.users.iter().any(|u| u.data.username == user_data.username) {
+            return Err(duplicate_entry(&user_data.username, "uk_username"));
+        }
+        if state.users.iter().any(|u| u.data.email == user_data.email) {
+            return Err(duplicate_entry(&user_data.email, "uk_email"));
+        }
+
+        state.next_user_id += 1;
+        let now = Utc::now();
+        let user = User {
+            id: state.next_user_id,
+            username: user_data.username.clone(),
+            email: user_data.email.clone(),
+            password_hash,
+            salt,
+            status: 1,
+            last_login_at: None,
+            last_login_ip: None,
+            login_count: 0,
+            created_at: now,
+            updated_at: now,
+        };
+        state.users.push(Row::new(user.clone()));
+
+        Ok(user)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<User, sqlx::Error> {
+        self.state().users.iter()
+            .find(|u| u.is_live() && u.data.id == id)
+            .map(|u| u.data.clone())
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<User, sqlx::Error> {
+        self.state().users.iter()
+            .find(|u| u.is_live() && u.data.username == username)
+            .map(|u| u.data.clone())
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn update_login_info(&self, user_id: i64, ip: &str) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+
+        if let Some(user) = self.state().users.iter_mut().find(|u| u.data.id == user_id) {
+            user.data.last_login_at = Some(now);
+            user.data.last_login_ip = Some(ip.to_string());
+            user.data.login_count += 1;
+            user.data.updated_at = now;
+        }
+
+        Ok(())
+    }
+
+    async fn update_password(&self, user_id: i64, password: &str) -> Result<(), sqlx::Error> {
+        let (password_hash, salt) = hash_password(password)?;
+        let mut state = self.state();
+        let user = state.users.iter_mut()
+            .find(|u| u.data.id == user_id && u.is_live())
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        user.data.password_hash = password_hash;
+        user.data.salt = salt;
+        user.data.updated_at = Utc::now();
+        Ok(())
+    }
+
+    async fn find_settings(&self, user_id: i64) -> Result<UserSettings, sqlx::Error> {
+        UserRepository::find_by_id(self, user_id).await?;
+        Ok(self.state().settings(user_id))
+    }
+
+    async fn update_settings(&self, user_id: i64, settings: &UserSettings) -> Result<UserSettings, sqlx::Error> {
+        let mut state = self.state();
+        state.settings.insert(user_id, settings.clone());
+
+        // 按新的保留数量清理该用户所有 Clips 的旧版本
+        let current: HashMap<i64, i32> = state.clips.iter()
+            .filter(|c| c.data.user_id == user_id)
+            .map(|c| (c.data.id, c.data.version))
+            .collect();
+        state.revisions.retain(|r| {
+            current.get(&r.clip_id)
+                .is_none_or(|version| r.version >= version - settings.revision_retention)
+        });
+
+        Ok(settings.clone())
+    }
+}
+
+#[async_trait]
+impl SessionRepository for MemoryStorage {
+    async fn create_session(&self, session: &NewSession) -> Result<UserSession, sqlx::Error> {
+        let mut state = self.state();
+
+        if state.sessions.iter().any(|s| s.token == session.token) {
+            return Err(duplicate_entry(&session.token, "uk_token"));
+        }
+        if state.sessions.iter().any(|s| s.refresh_token == session.refresh_token) {
+            return Err(duplicate_entry(&session.refresh_token, "uk_refresh_token"));
+        }
+
+        state.next_session_id += 1;
+        let user_session = UserSession {
+            id: state.next_session_id,
+            user_id: session.user_id,
+            token: session.token.clone(),
+            refresh_token: session.refresh_token.clone(),
+            family_id: session.family_id.clone(),
+            expires_at: session.expires_at,
+            refresh_expires_at: session.refresh_expires_at,
+            device_info: session.device_info.clone(),
+            ip_address: Some(session.ip_address.clone()),
+            last_seen_at: None,
+            created_at: Utc::now(),
+        };
+        state.sessions.push(user_session.clone());
+
+        Ok(user_session)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<UserSession, sqlx::Error> {
+        self.state().sessions.iter()
+            .find(|s| s.id == id)
+            .cloned()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn find_by_token(&self, token: &str) -> Result<UserSession, sqlx::Error> {
+        let now = Utc::now();
+
+        self.state().sessions.iter()
+            .find(|s| s.token == token && s.expires_at > now)
+            .cloned()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn find_by_refresh_token(&self, refresh_token: &str) -> Result<UserSession, sqlx::Error> {
+        let now = Utc::now();
+
+        self.state().sessions.iter()
+            .find(|s| s.refresh_token == refresh_token && s.refresh_expires_at > now)
+            .cloned()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn rotate_session(&self, id: i64, old_refresh_token: &str, tokens: &SessionTokens) -> Result<(), sqlx::Error> {
+        let mut state = self.state();
+        let session = state.sessions.iter_mut()
+            .find(|s| s.id == id && s.refresh_token == old_refresh_token)
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        session.token = tokens.token.clone();
+        session.refresh_token = tokens.refresh_token.clone();
+        session.expires_at = tokens.expires_at;
+        session.refresh_expires_at = tokens.refresh_expires_at;
+        Ok(())
+    }
+
+    async fn delete_family(&self, family_id: &str) -> Result<u64, sqlx::Error> {
+        let mut state = self.state();
+        let before = state.sessions.len();
+        state.sessions.retain(|s| s.family_id != family_id);
+
+        Ok((before - state.sessions.len()) as u64)
+    }
+
+    async fn delete_session(&self, token: &str) -> Result<(), sqlx::Error> {
+        self.state().sessions.retain(|s| s.token != token);
+        Ok(())
+    }
+
+    async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<UserSession>, sqlx::Error> {
+        let now = Utc::now();
+
+        let mut sessions: Vec<UserSession> = self.state().sessions.iter()
+            .filter(|s| s.user_id == user_id && s.refresh_expires_at > now)
+            .cloned()
+            .collect();
+        sessions.sort_by(|a, b| {
+            b.last_seen_at.unwrap_or(b.created_at).cmp(&a.last_seen_at.unwrap_or(a.created_at))
+                .then(b.id.cmp(&a.id))
+        });
+        Ok(sessions)
+    }
+
+    async fn delete_user_session(&self, user_id: i64, id: i64) -> Result<(), sqlx::Error> {
+        let mut state = self.state();
+        let before = state.sessions.len();
+        state.sessions.retain(|s| !(s.id == id && s.user_id == user_id));
+
+        if state.sessions.len() == before {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    async fn delete_other_sessions(&self, user_id: i64, keep_id: i64) -> Result<u64, sqlx::Error> {
+        let mut state = self.state();
+        let before = state.sessions.len();
+        state.sessions.retain(|s| s.user_id != user_id || s.id == keep_id);
+
+        Ok((before - state.sessions.len()) as u64)
+    }
+
+    async fn touch_session(&self, id: i64, seen_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        if let Some(session) = self.state().sessions.iter_mut().find(|s| s.id == id) {
+            session.last_seen_at = Some(seen_at);
+        }
+        Ok(())
+    }
+
+    async fn delete_expired_sessions(&self, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let mut state = self.state();
+        let before = state.sessions.len();
+        state.sessions.retain(|s| s.refresh_expires_at >= now);
+
+        Ok((before - state.sessions.len()) as u64)
+    }
+}
+
+#[async_trait]
+impl ClipRepository for MemoryStorage {
+    async fn create_clip(&self, user_id: i64, clip_data: &CreateClip) -> Result<ClipContent, sqlx::Error> {
+        let short_url = generate_short_url();
+        let mut state = self.state();
+
+        if state.clips.iter().any(|c| c.data.short_url.as_deref() == Some(short_url.as_str())) {
+            return Err(duplicate_entry(&short_url, "uk_short_url"));
+        }
+
+        state.next_clip_id += 1;
+        let now = Utc::now();
+        let clip = ClipContent {
+            id: state.next_clip_id,
+            user_id,
+            title: clip_data.title.clone(),
+            content: clip_data.content.clone(),
+            content_type: clip_data.content_type.clone().unwrap_or_else(|| "text".to_string()),
+            language: clip_data.language.clone(),
+            is_encrypted: clip_data.is_encrypted.unwrap_or(false) as i16,
+            encryption_key: None,
+            access_type: clip_data.access_type.clone().unwrap_or_else(|| "private".to_string()),
+            view_count: 0,
+            expires_at: clip_data.expires_at,
+            short_url: Some(short_url),
+            tags: tags_to_json(&clip_data.tags),
+            created_at: now,
+            updated_at: now,
+            version: 1,
+            max_views: clip_data.max_views,
+            share_url: None,
+            qr_code: None,
+        };
+        state.clips.push(Row::new(clip.clone()));
+        state.save_revision(&clip);
+
+        Ok(clip)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<ClipContent, sqlx::Error> {
+        self.state().clips.iter()
+            .find(|c| c.is_live() && c.data.id == id)
+            .map(|c| c.data.clone())
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn find_by_short_url(&self, short_url: &str) -> Result<ClipContent, sqlx::Error> {
+        self.state().clips.iter()
+            .find(|c| c.is_live() && c.data.short_url.as_deref() == Some(short_url))
+            .map(|c| c.data.clone())
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn find_by_user_id(&self, user_id: i64, filter: &ClipFilter, page: i64, page_size: i64) -> Result<Vec<ClipContent>, sqlx::Error> {
+        let offset = ((page - 1) * page_size).max(0) as usize;
+        let now = Utc::now();
+
+        let mut clips: Vec<ClipContent> = self.state().clips.iter()
+            .filter(|c| c.is_live() && c.data.user_id == user_id && matches_filter(&c.data, filter, now))
+            .map(|c| c.data.clone())
+            .collect();
+
+        // 与 clip_order_by 一致：排序值相同时按 id 保持稳定
+        clips.sort_by(|a, b| {
+            let ordering = match filter.sort {
+                ClipSort::Created => a.created_at.cmp(&b.created_at),
+                ClipSort::Updated => a.updated_at.cmp(&b.updated_at),
+                ClipSort::ViewCount => a.view_count.cmp(&b.view_count),
+                ClipSort::Title => a.title.as_deref().unwrap_or("").cmp(b.title.as_deref().unwrap_or("")),
+            }
+                .then(a.id.cmp(&b.id));
+
+            match filter.order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+        });
+
+        Ok(clips.into_iter().skip(offset).take(page_size.max(0) as usize).collect())
+    }
+
+    async fn count_by_user_id(&self, user_id: i64, filter: &ClipFilter) -> Result<i64, sqlx::Error> {
+        let now = Utc::now();
+
+        let count = self.state().clips.iter()
+            .filter(|c| c.is_live() && c.data.user_id == user_id && matches_filter(&c.data, filter, now))
+            .count();
+
+        Ok(count as i64)
+    }
+
+    async fn find_changes(
+        &self,
+        user_id: i64,
+        since: Option<(DateTime<Utc>, i64)>,
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<ClipChange>, sqlx::Error> {
+        let mut changes: Vec<ClipChange> = self.state().clips.iter()
+            .filter(|c| c.data.user_id == user_id && c.data.updated_at < until)
+            .filter(|c| match since {
+                Some(position) => (c.data.updated_at, c.data.id) > position,
+                None => c.is_live(),
+            })
+            .map(|c| ClipChange { clip: c.data.clone(), deleted_at: c.deleted_at })
+            .collect();
+
+        changes.sort_by_key(|change| (change.clip.updated_at, change.clip.id));
+        changes.truncate(limit.max(0) as usize);
+
+        Ok(changes)
+    }
+
+    async fn find_tombstones(
+        &self,
+        user_id: i64,
+        since: (DateTime<Utc>, i64),
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<ClipTombstone>, sqlx::Error> {
+        let mut tombstones: Vec<ClipTombstone> = self.state().tombstones.iter()
+            .filter(|(owner, t)| *owner == user_id && t.deleted_at < until && (t.deleted_at, t.id) > since)
+            .map(|(_, t)| t.clone())
+            .collect();
+
+        tombstones.sort_by_key(|t| (t.deleted_at, t.id));
+        tombstones.truncate(limit.max(0) as usize);
+        Ok(tombstones)
+    }
+
+    async fn delete_tombstones(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let mut state = self.state();
+        let count = state.tombstones.len();
+        state.tombstones.retain(|(_, t)| t.deleted_at >= before);
+
+        Ok((count - state.tombstones.len()) as u64)
+    }
+
+    async fn search(&self, user_id: i64, terms: &[String], now: DateTime<Utc>, limit: i64) -> Result<Vec<ClipContent>, sqlx::Error> {
+        let contains_all = |clip: &ClipContent| {
+            let title = clip.title.as_deref().unwrap_or("").to_lowercase();
+            let content = clip.content.to_lowercase();
+            terms.iter().all(|term| title.contains(term.as_str()) || content.contains(term.as_str()))
+        };
+
+        let mut clips: Vec<ClipContent> = self.state().clips.iter()
+            .filter(|c| c.is_live() && c.data.user_id == user_id && c.data.is_encrypted == 0)
+            .filter(|c| c.data.expires_at.is_none_or(|expires_at| expires_at > now) && contains_all(&c.data))
+            .map(|c| c.data.clone())
+            .collect();
+
+        clips.sort_by(|a, b| b.updated_at.cmp(&a.updated_at).then(b.id.cmp(&a.id)));
+        clips.truncate(limit.max(0) as usize);
+
+        Ok(clips)
+    }
+
+    async fn update_clip(
+        &self,
+        id: i64,
+        user_id: i64,
+        clip_data: &UpdateClip,
+        expected_version: Option<i32>,
+    ) -> Result<ClipContent, sqlx::Error> {
+        let mut state = self.state();
+
+        let clip = state.clips.iter_mut()
+            .find(|c| {
+                c.is_live()
+                    && c.data.id == id
+                    && c.data.user_id == user_id
+                    && expected_version.is_none_or(|version| version == c.data.version)
+            })
+            .ok_or(sqlx::Error::RowNotFound)?;
+        let clip = &mut clip.data;
+
+        if let Some(title) = &clip_data.title {
+            clip.title = title.clone();
+        }
+        if let Some(content) = &clip_data.content {
+            clip.content = content.clone();
+        }
+        if let Some(content_type) = &clip_data.content_type {
+            clip.content_type = content_type.clone();
+        }
+        if let Some(language) = &clip_data.language {
+            clip.language = language.clone();
+        }
+        if let Some(is_encrypted) = clip_data.is_encrypted {
+            clip.is_encrypted = is_encrypted as i16;
+        }
+        if let Some(access_type) = &clip_data.access_type {
+            clip.access_type = access_type.clone();
+        }
+        if let Some(expires_at) = clip_data.expires_at {
+            clip.expires_at = expires_at;
+        }
+        if let Some(tags) = &clip_data.tags {
+            clip.tags = tags_to_json(tags);
+        }
+        if let Some(max_views) = clip_data.max_views {
+            clip.max_views = max_views;
+        }
+        clip.updated_at = Utc::now();
+        clip.version += 1;
+
+        let clip = clip.clone();
+        state.save_revision(&clip);
+
+        Ok(clip)
+    }
+
+    async fn find_revisions(&self, clip_id: i64) -> Result<Vec<ClipRevisionSummary>, sqlx::Error> {
+        let mut revisions: Vec<ClipRevisionSummary> = self.state().revisions.iter()
+            .filter(|r| r.clip_id == clip_id)
+            .map(|r| ClipRevisionSummary {
+                clip_id: r.clip_id,
+                version: r.version,
+                title: r.title.clone(),
+                tags: r.tags.clone(),
+                created_at: r.created_at,
+            })
+            .collect();
+
+        revisions.sort_by_key(|r| std::cmp::Reverse(r.version));
+        Ok(revisions)
+    }
+
+    async fn find_revision(&self, clip_id: i64, version: i32) -> Result<ClipRevision, sqlx::Error> {
+        self.state().revisions.iter()
+            .find(|r| r.clip_id == clip_id && r.version == version)
+            .cloned()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn delete_clip(&self, id: i64, user_id: i64) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+
+        let mut state = self.state();
+        let clip = state.clips.iter_mut()
+            .find(|c| c.is_live() && c.data.id == id && c.data.user_id == user_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        clip.deleted_at = Some(now);
+        clip.data.updated_at = now;
+        Ok(())
+    }
+
+    async fn find_trashed(&self, user_id: i64, page: i64, page_size: i64) -> Result<Vec<TrashedClip>, sqlx::Error> {
+        let offset = ((page - 1) * page_size).max(0) as usize;
+
+        let mut clips: Vec<TrashedClip> = self.state().clips.iter()
+            .filter(|c| c.data.user_id == user_id)
+            .filter_map(|c| c.deleted_at.map(|deleted_at| TrashedClip { clip: c.data.clone(), deleted_at }))
+            .collect();
+
+        clips.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at).then(b.clip.id.cmp(&a.clip.id)));
+        Ok(clips.into_iter().skip(offset).take(page_size.max(0) as usize).collect())
+    }
+
+    async fn restore_clip(&self, id: i64, user_id: i64) -> Result<ClipContent, sqlx::Error> {
+        let mut state = self.state();
+        let clip = state.clips.iter_mut()
+            .find(|c| !c.is_live() && c.data.id == id && c.data.user_id == user_id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        clip.deleted_at = None;
+        clip.data.updated_at = Utc::now();
+        Ok(clip.data.clone())
+    }
+
+    async fn purge_clip(&self, id: i64, user_id: i64) -> Result<(), sqlx::Error> {
+        match self.state().purge_clips(|c| !c.is_live() && c.data.id == id && c.data.user_id == user_id) {
+            0 => Err(sqlx::Error::RowNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    async fn empty_trash(&self, user_id: i64) -> Result<u64, sqlx::Error> {
+        Ok(self.state().purge_clips(|c| !c.is_live() && c.data.user_id == user_id))
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        Ok(self.state().purge_clips(|c| c.deleted_at.is_some_and(|deleted_at| deleted_at < before)))
+    }
+
+    async fn delete_expired_clips(&self, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        Ok(self.state().purge_clips(|c| c.data.expires_at.is_some_and(|expires_at| expires_at <= now)))
+    }
+
+    async fn delete_access_logs(&self, _before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        // 内存存储不记录访问日志
+        Ok(0)
+    }
+
+    async fn view_clip(&self, id: i64) -> Result<ClipContent, sqlx::Error> {
+        let mut state = self.state();
+        let clip = state.clips.iter_mut()
+            .find(|c| c.is_live() && c.data.id == id)
+            .filter(|c| c.data.max_views.is_none_or(|max_views| c.data.view_count < max_views))
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        clip.data.view_count += 1;
+        let clip = clip.data.clone();
+
+        if clip.max_views.is_some_and(|max_views| clip.view_count >= max_views) {
+            state.purge_clips(|c| c.data.id == id);
+        }
+        Ok(clip)
+    }
+}
+
+#[async_trait]
+impl AuditRepository for MemoryStorage {
+    /// 内存存储不保留审计日志，只输出到运行日志
+    async fn record_event(&self, event: &NewAuditEvent) -> Result<(), sqlx::Error> {
+        tracing::info!("审计事件: {:?}", event);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for MemoryStorage {
+    async fn register_attempt(
+        &self,
+        key: &str,
+        now: DateTime<Utc>,
+        window_start: DateTime<Utc>,
+        lock_until: &(dyn Fn(i32) -> Option<DateTime<Utc>> + Sync),
+    ) -> Result<AttemptOutcome, sqlx::Error> {
+        let mut state = self.state();
+        let attempt = state.login_attempts.entry(key.to_string())
+            .or_insert(LoginAttempt { failures: 0, last_failed_at: now, locked_until: None });
+
+        if let Some(locked_until) = attempt.locked_until.filter(|until| *until > now) {
+            return Ok(AttemptOutcome::Locked(locked_until));
+        }
+
+        attempt.failures = if attempt.last_failed_at < window_start { 1 } else { attempt.failures + 1 };
+        attempt.last_failed_at = now;
+        attempt.locked_until = lock_until(attempt.failures);
+        Ok(AttemptOutcome::Counted { failures: attempt.failures, locked_until: attempt.locked_until })
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), sqlx::Error> {
+        self.state().login_attempts.remove(key);
+        Ok(())
+    }
+
+    async fn undo_attempt(&self, key: &str) -> Result<(), sqlx::Error> {
+        if let Some(attempt) = self.state().login_attempts.get_mut(key) {
+            attempt.failures = (attempt.failures - 1).max(0);
+        }
+        Ok(())
+    }
+
+    async fn delete_stale_attempts(&self, before: DateTime<Utc>, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let mut state = self.state();
+        let count = state.login_attempts.len();
+        state.login_attempts.retain(|_, attempt| {
+            attempt.last_failed_at >= before || attempt.locked_until.is_some_and(|until| until >= now)
+        });
+
+        Ok((count - state.login_attempts.len()) as u64)
+    }
+}
+
+#[async_trait]
+impl JobRepository for MemoryStorage {
+    async fn try_lock(&self, name: &str, owner: &str, until: DateTime<Utc>) -> Result<bool, sqlx::Error> {
+        let now = Utc::now();
+        let mut state = self.state();
+
+        match state.job_locks.get(name) {
+            Some((holder, locked_until)) if holder != owner && *locked_until > now => Ok(false),
+            _ => {
+                state.job_locks.insert(name.to_string(), (owner.to_string(), until));
+                Ok(true)
+            }
+        }
+    }
+}
+
+/// 对应 SQL 后端 push_clip_filter 的筛选条件
+fn matches_filter(clip: &ClipContent, filter: &ClipFilter, now: DateTime<Utc>) -> bool {
+    let has_tag = |tag: &String| {
+        clip.tags.as_ref()
+            .and_then(|tags| tags.as_array())
+            .is_some_and(|tags| tags.iter().any(|t| t.as_str() == Some(tag.as_str())))
+    };
+    let expired = clip.expires_at.is_some_and(|expires_at| expires_at <= now);
+
+    filter.content_type.as_ref().is_none_or(|v| *v == clip.content_type)
+        && filter.language.as_ref().is_none_or(|v| clip.language.as_ref() == Some(v))
+        && filter.access_type.as_ref().is_none_or(|v| *v == clip.access_type)
+        && filter.tag.as_ref().is_none_or(has_tag)
+        && filter.created_from.is_none_or(|t| clip.created_at >= t)
+        && filter.created_to.is_none_or(|t| clip.created_at <= t)
+        && filter.updated_from.is_none_or(|t| clip.updated_at >= t)
+        && filter.updated_to.is_none_or(|t| clip.updated_at <= t)
+        && filter.expired == expired
+}
@@ -0,0 +1,51 @@
+use sqlx::{
+    migrate::{Migrate, Migrator},
+    Database, Pool,
+};
+use std::collections::HashSet;
+
+/// 各后端的迁移脚本，编译时嵌入二进制
+pub static MYSQL: Migrator = sqlx::migrate!("./migrations/mysql");
+pub static POSTGRES: Migrator = sqlx::migrate!("./migrations/postgres");
+pub static SQLITE: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+/// 应用或校验数据库结构迁移，返回当前的结构版本
+///
+/// 各后端的迁移编号相互独立（如 PostgreSQL 多一个 updated_at 触发器迁移），版本号只在同一后端内可比较，
+/// 报告版本时需同时给出后端名称。
+///
+/// - 数据库中记录的版本比程序内置的更新时拒绝启动，避免旧程序写坏新结构；
+/// - `apply` 为 false 时只做校验，存在未执行的迁移同样拒绝启动。
+pub async fn run<DB>(backend: &str, migrator: &Migrator, pool: &Pool<DB>, apply: bool) -> Result<i64, sqlx::Error>
+where
+    DB: Database,
+    DB::Connection: Migrate,
+{
+    let latest = migrator.iter().map(|m| m.version).max().unwrap_or(0);
+
+    let applied = {
+        let mut conn = pool.acquire().await?;
+        conn.ensure_migrations_table().await?;
+        conn.list_applied_migrations().await?
+    };
+    let applied: HashSet<i64> = applied.into_iter().map(|m| m.version).collect();
+
+    if let Some(newer) = applied.iter().copied().filter(|v| *v > latest).max() {
+        return Err(sqlx::Error::Configuration(
+            format!("{} 数据库结构版本 {} 高于当前程序支持的版本 {}，请升级服务端", backend, newer, latest).into(),
+        ));
+    }
+
+    if apply {
+        migrator.run(pool).await?;
+        return Ok(latest);
+    }
+
+    if let Some(pending) = migrator.iter().find(|m| !applied.contains(&m.version)) {
+        return Err(sqlx::Error::Configuration(
+            format!("{} 数据库结构缺少迁移 {}（{}），请先执行迁移", backend, pending.version, pending.description).into(),
+        ));
+    }
+
+    Ok(latest)
+}
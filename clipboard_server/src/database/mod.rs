@@ -1,94 +1,110 @@
-use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
-use std::time::Duration;
+use async_trait::async_trait;
 use bcrypt::{hash, verify, DEFAULT_COST};
-use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use crate::config::Config;
+use std::{ops::Deref, sync::{Arc, OnceLock}};
+use uuid::Uuid;
+use crate::config::{Config, DatabaseBackend};
 
+pub mod memory;
+pub mod migrate;
 pub mod models;
+pub mod mysql;
+pub mod postgres;
+pub mod sqlite;
 pub use models::*;
 
-pub type DbPool = MySqlPool;
+/// 数据库连接池，内部持有按 DATABASE_URL 选择的存储后端
+#[derive(Clone)]
+pub struct DbPool(Arc<dyn Storage>);
+
+impl Deref for DbPool {
+    type Target = dyn Storage;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
 
-/// 初始化 MySQL 连接池
+/// 初始化数据库连接池，并应用（或校验）内置的结构迁移
 pub async fn init_pool(config: &Config) -> Result<DbPool, sqlx::Error> {
-    MySqlPoolOptions::new()
-        .max_connections(10)
-        .acquire_timeout(Duration::from_secs(30))
-        .connect(&config.database_url.as_str())
-        .await
+    let storage: Arc<dyn Storage> = match config.database_backend {
+        DatabaseBackend::MySql => Arc::new(mysql::MySqlStorage::connect(&config.database_url).await?),
+        DatabaseBackend::Postgres => Arc::new(postgres::PostgresStorage::connect(&config.database_url).await?),
+        DatabaseBackend::Sqlite => Arc::new(sqlite::SqliteStorage::connect(&config.database_url).await?),
+        DatabaseBackend::Memory => Arc::new(memory::MemoryStorage::new()),
+    };
+
+    let version = storage.migrate(config.database_auto_migrate).await?;
+    // 各后端的迁移编号相互独立，版本号需带上后端名称
+    tracing::info!("{} 数据库结构版本：{}", config.database_backend.name(), version);
+
+    Ok(DbPool(storage))
 }
 
 /// 健康检查 - 测试数据库连接
 pub async fn check_database_health(pool: &DbPool) -> Result<(), sqlx::Error> {
-    let _: (i32,) = sqlx::query_as("SELECT 1")
-        .fetch_one(pool)
-        .await?;
-    Ok(())
+    pool.health_check().await
 }
 
-/// 用户相关操作
-pub struct UserRepository;
+/// 存储后端，每种数据库各自实现一套仓库
+#[async_trait]
+pub trait Storage: Send + Sync {
+    fn users(&self) -> &dyn UserRepository;
+    fn sessions(&self) -> &dyn SessionRepository;
+    fn clips(&self) -> &dyn ClipRepository;
+    fn jobs(&self) -> &dyn JobRepository;
+    fn audit(&self) -> &dyn AuditRepository;
+    fn login_attempts(&self) -> &dyn LoginAttemptRepository;
+
+    /// 应用或校验结构迁移，返回当前结构版本（各后端独立编号）
+    async fn migrate(&self, apply: bool) -> Result<i64, sqlx::Error>;
+
+    /// 测试数据库连接
+    async fn health_check(&self) -> Result<(), sqlx::Error>;
+}
 
-impl UserRepository {
+/// 用户相关操作
+#[async_trait]
+pub trait UserRepository: Send + Sync {
     /// 创建用户
-    pub async fn create_user(pool: &DbPool, user_data: &CreateUser) -> Result<User, sqlx::Error> {
-        let salt = Uuid::new_v4().to_string();
-        let password_hash = hash(&format!("{}{}", user_data.password, salt), DEFAULT_COST)
-            .map_err(|e| sqlx::Error::Protocol(e.to_string().into()))?;
-
-        let result = sqlx::query!(
-            r#"
-            INSERT INTO clip_users (username, email, password_hash, salt)
-            VALUES (?, ?, ?, ?)
-            "#,
-            user_data.username,
-            user_data.email,
-            password_hash,
-            salt
-        )
-            .execute(pool)
-            .await?;
-
-        Self::find_by_id(pool, result.last_insert_id() as i64).await
-    }
+    async fn create_user(&self, user_data: &CreateUser) -> Result<User, sqlx::Error>;
 
     /// 根据ID查找用户
-    pub async fn find_by_id(pool: &DbPool, id: i64) -> Result<User, sqlx::Error> {
-        sqlx::query_as(
-            r#"
-            SELECT id, username, email, password_hash, salt, status,
-                   last_login_at, last_login_ip, login_count, created_at, updated_at
-            FROM clip_users
-            WHERE id = ? AND deleted_at IS NULL
-            "#,
-        )
-            .bind(id)
-            .fetch_one(pool)
-            .await
-    }
+    async fn find_by_id(&self, id: i64) -> Result<User, sqlx::Error>;
 
     /// 根据用户名查找用户
-    pub async fn find_by_username(pool: &DbPool, username: &str) -> Result<User, sqlx::Error> {
-        sqlx::query_as(
-            r#"
-            SELECT id, username, email, password_hash, salt, status,
-                   last_login_at, last_login_ip, login_count, created_at, updated_at
-            FROM clip_users
-            WHERE username = ? AND deleted_at IS NULL
-            "#,
-        )
-            .bind(username)
-            .fetch_one(pool)
-            .await
-    }
+    async fn find_by_username(&self, username: &str) -> Result<User, sqlx::Error>;
 
-    /// 验证用户密码
-    pub async fn verify_password(pool: &DbPool, username: &str, password: &str) -> Result<User, sqlx::Error> {
-        let user = Self::find_by_username(pool, username).await?;
+    /// 更新用户登录信息
+    async fn update_login_info(&self, user_id: i64, ip: &str) -> Result<(), sqlx::Error>;
+
+    /// 获取用户设置
+    async fn find_settings(&self, user_id: i64) -> Result<UserSettings, sqlx::Error>;
 
-        let is_valid = verify(&format!("{}{}", password, user.salt), &user.password_hash)
-            .map_err(|e| sqlx::Error::Protocol(e.to_string().into()))?;
+    /// 重新生成盐值并更新密码哈希
+    async fn update_password(&self, user_id: i64, password: &str) -> Result<(), sqlx::Error>;
+
+    /// 保存用户设置，并按新的保留数量清理该用户多余的历史版本
+    async fn update_settings(&self, user_id: i64, settings: &UserSettings) -> Result<UserSettings, sqlx::Error>;
+
+    /// 验证用户密码
+    ///
+    /// 用户不存在时也对一个固定哈希做一次校验，避免通过响应耗时判断用户名是否存在。
+    async fn verify_password(&self, username: &str, password: &str) -> Result<User, sqlx::Error> {
+        static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+        let user = match self.find_by_username(username).await {
+            Ok(user) => user,
+            Err(sqlx::Error::RowNotFound) => {
+                let dummy_hash = DUMMY_HASH.get_or_init(|| hash("dummy-password", DEFAULT_COST).unwrap_or_default());
+                let _ = verify(password, dummy_hash);
+                return Err(sqlx::Error::RowNotFound);
+            }
+            Err(e) => return Err(e),
+        };
+
+        let is_valid = verify(format!("{}{}", password, user.salt), &user.password_hash)
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         if is_valid {
             Ok(user)
@@ -96,241 +112,227 @@ impl UserRepository {
             Err(sqlx::Error::RowNotFound)
         }
     }
-
-    /// 更新用户登录信息
-    pub async fn update_login_info(pool: &DbPool, user_id: i64, ip: &str) -> Result<(), sqlx::Error> {
-        sqlx::query(
-            r#"
-            UPDATE clip_users
-            SET last_login_at = NOW(), last_login_ip = ?, login_count = login_count + 1
-            WHERE id = ?
-            "#,
-        )
-            .bind(ip)
-            .bind(user_id)
-            .execute(pool)
-            .await?;
-
-        Ok(())
-    }
 }
 
 /// 会话相关操作
-pub struct SessionRepository;
-
-impl SessionRepository {
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
     /// 创建会话
-    pub async fn create_session(
-        pool: &DbPool,
-        user_id: i64,
-        token: &str,
-        refresh_token: &str,
-        expires_at: DateTime<Utc>,
-        refresh_expires_at: DateTime<Utc>,
-        ip: &str,
-        device_info: Option<String>,
-    ) -> Result<UserSession, sqlx::Error> {
-        let result = sqlx::query!(
-            r#"
-            INSERT INTO clip_user_sessions (user_id, token, refresh_token, expires_at, refresh_expires_at, ip_address, device_info)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
-            "#,
-            user_id,
-            token,
-            refresh_token,
-            expires_at,
-            refresh_expires_at,
-            ip,
-            device_info
-        )
-            .execute(pool)
-            .await?;
-
-        Self::find_by_id(pool, result.last_insert_id() as i64).await
-    }
+    async fn create_session(&self, session: &NewSession) -> Result<UserSession, sqlx::Error>;
 
     /// 根据ID查找会话
-    pub async fn find_by_id(pool: &DbPool, id: i64) -> Result<UserSession, sqlx::Error> {
-        sqlx::query_as(
-            r#"
-            SELECT id, user_id, token, refresh_token, expires_at, refresh_expires_at, device_info, ip_address, created_at
-            FROM clip_user_sessions
-            WHERE id = ?
-            "#,
-        )
-            .bind(id)
-            .fetch_one(pool)
-            .await
-    }
+    async fn find_by_id(&self, id: i64) -> Result<UserSession, sqlx::Error>;
 
     /// 根据token查找会话
-    pub async fn find_by_token(pool: &DbPool, token: &str) -> Result<UserSession, sqlx::Error> {
-        sqlx::query_as(
-            r#"
-            SELECT id, user_id, token, refresh_token, expires_at, refresh_expires_at, device_info, ip_address, created_at
-            FROM clip_user_sessions
-            WHERE token = ? AND expires_at > NOW()
-            "#,
-        )
-            .bind(token)
-            .fetch_one(pool)
-            .await
-    }
+    async fn find_by_token(&self, token: &str) -> Result<UserSession, sqlx::Error>;
+
+    /// 根据未过期的 refresh token 查找会话
+    async fn find_by_refresh_token(&self, refresh_token: &str) -> Result<UserSession, sqlx::Error>;
+
+    /// 轮换会话令牌；refresh token 已不是 old_refresh_token（已被轮换）时返回 RowNotFound
+    async fn rotate_session(&self, id: i64, old_refresh_token: &str, tokens: &SessionTokens) -> Result<(), sqlx::Error>;
+
+    /// 吊销整个令牌族，返回删除的会话数
+    async fn delete_family(&self, family_id: &str) -> Result<u64, sqlx::Error>;
 
     /// 删除会话
-    pub async fn delete_session(pool: &DbPool, token: &str) -> Result<(), sqlx::Error> {
-        sqlx::query(
-            r#"
-            DELETE FROM clip_user_sessions WHERE token = ?
-            "#,
-        )
-            .bind(token)
-            .execute(pool)
-            .await?;
-
-        Ok(())
-    }
+    async fn delete_session(&self, token: &str) -> Result<(), sqlx::Error>;
+
+    /// 用户未过期的会话，按最近活跃时间倒序
+    async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<UserSession>, sqlx::Error>;
+
+    /// 删除用户的指定会话，不存在时返回 RowNotFound
+    async fn delete_user_session(&self, user_id: i64, id: i64) -> Result<(), sqlx::Error>;
+
+    /// 删除用户除 keep_id 以外的所有会话，返回删除数量
+    async fn delete_other_sessions(&self, user_id: i64, keep_id: i64) -> Result<u64, sqlx::Error>;
+
+    /// 更新会话最近活跃时间
+    async fn touch_session(&self, id: i64, seen_at: DateTime<Utc>) -> Result<(), sqlx::Error>;
+
+    /// 删除 refresh token 已过期的会话，返回删除数量
+    async fn delete_expired_sessions(&self, now: DateTime<Utc>) -> Result<u64, sqlx::Error>;
 }
 
 /// Clip 内容相关操作
-pub struct ClipRepository;
-
-impl ClipRepository {
-    /// 创建 Clip
-    pub async fn create_clip(pool: &DbPool, user_id: i64, clip_data: &CreateClip) -> Result<ClipContent, sqlx::Error> {
-        let short_url = Some(Uuid::new_v4().to_string()[..8].to_string());
-        let tags_json = clip_data.tags.as_ref().map(|tags| serde_json::to_value(tags).unwrap());
-
-        let result = sqlx::query!(
-            r#"
-            INSERT INTO clip_contents (user_id, title, content, content_type, language, is_encrypted, access_type, short_url, tags)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-            user_id,
-            clip_data.title,
-            clip_data.content,
-            clip_data.content_type.as_deref().unwrap_or("text"),
-            clip_data.language,
-            clip_data.is_encrypted.unwrap_or(false) as i8,
-            clip_data.access_type.as_deref().unwrap_or("private"),
-            short_url,
-            tags_json
-        )
-            .execute(pool)
-            .await?;
-
-        Self::find_by_id(pool, result.last_insert_id() as i64).await
-    }
+#[async_trait]
+pub trait ClipRepository: Send + Sync {
+    /// 创建 Clip，初始内容作为第一条历史版本
+    async fn create_clip(&self, user_id: i64, clip_data: &CreateClip) -> Result<ClipContent, sqlx::Error>;
 
     /// 根据ID查找 Clip
-    pub async fn find_by_id(pool: &DbPool, id: i64) -> Result<ClipContent, sqlx::Error> {
-        sqlx::query_as(
-            r#"
-            SELECT id, user_id, title, content, content_type, language, is_encrypted, encryption_key,
-                   access_type, view_count, expires_at, short_url, tags, created_at, updated_at
-            FROM clip_contents
-            WHERE id = ? AND deleted_at IS NULL
-            "#,
-        )
-            .bind(id)
-            .fetch_one(pool)
-            .await
-    }
+    async fn find_by_id(&self, id: i64) -> Result<ClipContent, sqlx::Error>;
 
-    /// 根据短链接查找 Clip
-    pub async fn find_by_short_url(pool: &DbPool, short_url: &String) -> Result<ClipContent, sqlx::Error> {
-        sqlx::query_as(
-            r#"
-            SELECT id, user_id, title, content, content_type, language, is_encrypted, encryption_key,
-                   access_type, view_count, expires_at, short_url, tags, created_at, updated_at
-            FROM clip_contents
-            WHERE short_url = ? AND deleted_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())
-            "#,
-        )
-            .bind(short_url)
-            .fetch_one(pool)
-            .await
-    }
+    /// 根据短链接查找 Clip（含已过期的，由业务层判断）
+    async fn find_by_short_url(&self, short_url: &str) -> Result<ClipContent, sqlx::Error>;
 
-    /// 获取用户的 Clips
-    pub async fn find_by_user_id(pool: &DbPool, user_id: i64, page: i64, page_size: i64) -> Result<Vec<ClipContent>, sqlx::Error> {
-        let offset = (page - 1) * page_size;
-
-        sqlx::query_as(
-            r#"
-            SELECT id, user_id, title, content, content_type, language, is_encrypted, encryption_key,
-                   access_type, view_count, expires_at, short_url, tags, created_at, updated_at
-            FROM clip_contents
-            WHERE user_id = ? AND deleted_at IS NULL
-            ORDER BY created_at DESC
-            LIMIT ? OFFSET ?
-            "#,
-        )
-            .bind(user_id)
-            .bind(page_size)
-            .bind(offset)
-            .fetch_all(pool)
-            .await
-    }
+    /// 按筛选条件分页获取用户的 Clips
+    async fn find_by_user_id(&self, user_id: i64, filter: &ClipFilter, page: i64, page_size: i64) -> Result<Vec<ClipContent>, sqlx::Error>;
 
-    /// 更新 Clip
-    pub async fn update_clip(pool: &DbPool, id: i64, user_id: i64, clip_data: &UpdateClip) -> Result<ClipContent, sqlx::Error> {
-        let tags_json = clip_data.tags.as_ref().map(|tags| serde_json::to_value(tags).unwrap());
-
-        sqlx::query(
-            r#"
-            UPDATE clip_contents
-            SET title = COALESCE(?, title),
-                content = COALESCE(?, content),
-                content_type = COALESCE(?, content_type),
-                language = ?,
-                access_type = COALESCE(?, access_type),
-                expires_at = ?,
-                tags = COALESCE(?, tags)
-            WHERE id = ? AND user_id = ?
-            "#,
-        )
-            .bind(&clip_data.title)
-            .bind(&clip_data.content)
-            .bind(&clip_data.content_type)
-            .bind(&clip_data.language)
-            .bind(&clip_data.access_type)
-            .bind(&clip_data.expires_at)
-            .bind(&tags_json)
-            .bind(id)
-            .bind(user_id)
-            .execute(pool)
-            .await?;
-
-        Self::find_by_id(pool, id).await
-    }
+    /// 统计用户符合筛选条件的 Clips 数量
+    async fn count_by_user_id(&self, user_id: i64, filter: &ClipFilter) -> Result<i64, sqlx::Error>;
 
-    /// 删除 Clip（软删除）
-    pub async fn delete_clip(pool: &DbPool, id: i64, user_id: i64) -> Result<(), sqlx::Error> {
-        sqlx::query(
-            r#"
-            UPDATE clip_contents SET deleted_at = NOW() WHERE id = ? AND user_id = ?
-            "#,
-        )
-            .bind(id)
-            .bind(user_id)
-            .execute(pool)
-            .await?;
-
-        Ok(())
-    }
+    /// 增量同步：按 (updated_at, id) 顺序返回位置在 since 之后、updated_at 早于 until 的变更
+    ///
+    /// since 为空时是首次同步，只返回未删除的 Clips；否则已删除的记录也会返回，作为墓碑下发。
+    async fn find_changes(
+        &self,
+        user_id: i64,
+        since: Option<(DateTime<Utc>, i64)>,
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<ClipChange>, sqlx::Error>;
+
+    /// 增量同步：按 (deleted_at, clip_id) 顺序返回位置在 since 之后、deleted_at 早于 until 的彻底删除墓碑
+    async fn find_tombstones(
+        &self,
+        user_id: i64,
+        since: (DateTime<Utc>, i64),
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<ClipTombstone>, sqlx::Error>;
+
+    /// 删除 before 之前的墓碑，返回删除数量
+    async fn delete_tombstones(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error>;
+
+    /// 全文搜索候选集：用户自己的、截至 now 未过期、未加密且标题或内容包含全部关键词的 Clips，
+    /// 按更新时间倒序取前 limit 条（排序与高亮由业务层完成）
+    ///
+    /// 各后端都按不区分大小写的子串匹配（LIKE / ILIKE），不依赖数据库分词，同一查询的结果一致；
+    /// 只有 SQLite 的 LIKE 对非 ASCII 字母区分大小写。
+    /// 加密内容是密文，在查询中排除，避免占用候选名额。
+    async fn search(&self, user_id: i64, terms: &[String], now: DateTime<Utc>, limit: i64) -> Result<Vec<ClipContent>, sqlx::Error>;
+
+    /// 更新 Clip 并递增版本号，新版本同时写入历史版本表
+    ///
+    /// expected_version 非空时只在当前版本一致时更新；未更新任何记录（不存在、无权访问或版本不匹配）返回 RowNotFound。
+    async fn update_clip(
+        &self,
+        id: i64,
+        user_id: i64,
+        clip_data: &UpdateClip,
+        expected_version: Option<i32>,
+    ) -> Result<ClipContent, sqlx::Error>;
 
-    /// 增加查看次数
-    pub async fn increment_view_count(pool: &DbPool, id: i64) -> Result<(), sqlx::Error> {
-        sqlx::query(
-            r#"
-            UPDATE clip_contents SET view_count = view_count + 1 WHERE id = ?
-            "#,
-        )
-            .bind(id)
-            .execute(pool)
-            .await?;
-
-        Ok(())
-    }
-}
\ No newline at end of file
+    /// 获取 Clip 的历史版本列表（按版本号倒序）
+    async fn find_revisions(&self, clip_id: i64) -> Result<Vec<ClipRevisionSummary>, sqlx::Error>;
+
+    /// 获取 Clip 的指定历史版本
+    async fn find_revision(&self, clip_id: i64, version: i32) -> Result<ClipRevision, sqlx::Error>;
+
+    /// 删除 Clip（软删除），不存在、无权访问或已在回收站中时返回 RowNotFound
+    async fn delete_clip(&self, id: i64, user_id: i64) -> Result<(), sqlx::Error>;
+
+    /// 分页获取用户回收站中的 Clips（按删除时间倒序）
+    async fn find_trashed(&self, user_id: i64, page: i64, page_size: i64) -> Result<Vec<TrashedClip>, sqlx::Error>;
+
+    /// 从回收站恢复 Clip，不在回收站中时返回 RowNotFound
+    async fn restore_clip(&self, id: i64, user_id: i64) -> Result<ClipContent, sqlx::Error>;
+
+    /// 彻底删除回收站中的 Clip，不在回收站中时返回 RowNotFound
+    ///
+    /// 彻底删除（含下面的清空回收站、自动清理与阅后即焚）都会写入墓碑，供增量同步下发。
+    async fn purge_clip(&self, id: i64, user_id: i64) -> Result<(), sqlx::Error>;
+
+    /// 清空用户的回收站，返回删除数量
+    async fn empty_trash(&self, user_id: i64) -> Result<u64, sqlx::Error>;
+
+    /// 彻底删除所有用户在 before 之前放入回收站的 Clips，返回删除数量
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error>;
+
+    /// 彻底删除截至 now 已过期的 Clips（含回收站中的），返回删除数量
+    async fn delete_expired_clips(&self, now: DateTime<Utc>) -> Result<u64, sqlx::Error>;
+
+    /// 删除 before 之前的访问日志，返回删除数量
+    async fn delete_access_logs(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error>;
+
+    /// 记录一次查看并返回查看后的 Clip；已达到查看上限时返回 RowNotFound，
+    /// 本次查看达到上限时彻底删除该 Clip（不进入回收站）
+    async fn view_clip(&self, id: i64) -> Result<ClipContent, sqlx::Error>;
+}
+
+/// 定时任务锁
+#[async_trait]
+pub trait JobRepository: Send + Sync {
+    /// 尝试获取任务锁并设置到期时间；锁空闲、已到期或本来就由 owner 持有时成功
+    async fn try_lock(&self, name: &str, owner: &str, until: DateTime<Utc>) -> Result<bool, sqlx::Error>;
+}
+
+/// 安全审计日志
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    /// 记录一条审计事件
+    async fn record_event(&self, event: &NewAuditEvent) -> Result<(), sqlx::Error>;
+}
+
+/// 登录失败计数
+#[async_trait]
+pub trait LoginAttemptRepository: Send + Sync {
+    /// 在验证密码之前登记一次尝试，先按失败计数，验证成功后再 clear
+    ///
+    /// 处于锁定中时不计数，直接返回锁定到期时间；否则失败次数加一（上次失败早于 window_start 时从 1 重新计数），
+    /// lock_until 根据加一后的次数给出锁定到期时间。读取、计数与锁定在同一事务内完成并锁住该行，
+    /// 并发的尝试只能依次计数，不会越过上限。
+    async fn register_attempt(
+        &self,
+        key: &str,
+        now: DateTime<Utc>,
+        window_start: DateTime<Utc>,
+        lock_until: &(dyn Fn(i32) -> Option<DateTime<Utc>> + Sync),
+    ) -> Result<AttemptOutcome, sqlx::Error>;
+
+    /// 清除计数（验证成功时）
+    async fn clear(&self, key: &str) -> Result<(), sqlx::Error>;
+
+    /// 撤销一次已登记的尝试（验证成功时）：失败次数减一，不清除其余失败，也不解除已有的锁定
+    async fn undo_attempt(&self, key: &str) -> Result<(), sqlx::Error>;
+
+    /// 删除最近失败早于 before 且未处于锁定中的记录，返回删除数量
+    async fn delete_stale_attempts(&self, before: DateTime<Utc>, now: DateTime<Utc>) -> Result<u64, sqlx::Error>;
+}
+
+/// 生成密码盐值与哈希，返回 (password_hash, salt)
+pub(crate) fn hash_password(password: &str) -> Result<(String, String), sqlx::Error> {
+    let salt = Uuid::new_v4().to_string();
+    let password_hash = hash(format!("{}{}", password, salt), DEFAULT_COST)
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+    Ok((password_hash, salt))
+}
+
+/// 生成短链接
+pub(crate) fn generate_short_url() -> String {
+    Uuid::new_v4().to_string()[..8].to_string()
+}
+
+/// 将关键词转为 LIKE 模式，转义其中的通配符（转义符为反斜杠）
+pub(crate) fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("%{}%", escaped)
+}
+
+/// Clip 列表的 ORDER BY 子句，排序值相同时按 id 保持稳定
+pub(crate) fn clip_order_by(filter: &ClipFilter) -> String {
+    let column = match filter.sort {
+        ClipSort::Created => "created_at",
+        ClipSort::Updated => "updated_at",
+        ClipSort::ViewCount => "view_count",
+        // 各数据库对 NULL 的排序位置不同，统一按空标题处理
+        ClipSort::Title => "COALESCE(title, '')",
+    };
+    let direction = match filter.order {
+        SortOrder::Asc => "ASC",
+        SortOrder::Desc => "DESC",
+    };
+
+    format!(" ORDER BY {} {}, id {}", column, direction, direction)
+}
+
+/// 标签数组序列化为 JSON
+pub(crate) fn tags_to_json(tags: &Option<Vec<String>>) -> Option<serde_json::Value> {
+    tags.as_ref().map(|tags| serde_json::to_value(tags).unwrap())
+}
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use chrono::{DateTime, Utc};
 
 // 健康检查模型
@@ -10,14 +10,17 @@ pub struct HealthCheck {
 }
 
 // 用户相关模型
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct User {
     pub id: i64,
     pub username: String,
     pub email: String,
+    /// 密码哈希与盐不随用户信息返回给客户端
+    #[serde(skip_serializing)]
     pub password_hash: String,
+    #[serde(skip_serializing)]
     pub salt: String,
-    pub status: i8,
+    pub status: i16,
     pub last_login_at: Option<DateTime<Utc>>,
     pub last_login_ip: Option<String>,
     pub login_count: i32,
@@ -38,21 +41,82 @@ pub struct LoginUser {
     pub password: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Deserialize)]
+pub struct ChangePassword {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+// 用户设置
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserSettings {
+    /// 每个 Clip 保留的历史版本数（不含当前版本）
+    pub revision_retention: i32,
+    /// 新建 Clip 未指定有效期时使用的默认有效期（如 `1d`），为空表示永久有效
+    pub default_expires_in: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserSettings {
+    pub revision_retention: Option<i32>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub default_expires_in: Option<Option<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct UserSession {
     pub id: i64,
     pub user_id: i64,
     pub token: String,
     pub refresh_token: String,
+    /// 令牌族ID，同一次登录轮换出的令牌共用
+    pub family_id: String,
     pub expires_at: DateTime<Utc>,
     pub refresh_expires_at: DateTime<Utc>,
     pub device_info: Option<String>,
     pub ip_address: Option<String>,
+    /// 最近活跃时间，按间隔更新，不精确到每次请求
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 会话列表中的一项，不包含令牌
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+    pub id: i64,
+    /// 由 User-Agent 解析出的设备名，如 "Chrome 120 · Windows"
+    pub device: String,
+    pub device_info: Option<String>,
+    pub ip_address: Option<String>,
     pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// 是否为发起本次请求的会话
+    pub current: bool,
+}
+
+#[derive(Debug)]
+pub struct NewSession {
+    pub user_id: i64,
+    pub token: String,
+    pub refresh_token: String,
+    pub family_id: String,
+    pub expires_at: DateTime<Utc>,
+    pub refresh_expires_at: DateTime<Utc>,
+    pub ip_address: String,
+    pub device_info: Option<String>,
+}
+
+/// 轮换 refresh token 时写入会话的新令牌
+#[derive(Debug)]
+pub struct SessionTokens {
+    pub token: String,
+    pub refresh_token: String,
+    pub expires_at: DateTime<Utc>,
+    pub refresh_expires_at: DateTime<Utc>,
 }
 
 // Clip 相关模型
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct ClipContent {
     pub id: i64,
     pub user_id: i64,
@@ -60,7 +124,9 @@ pub struct ClipContent {
     pub content: String,
     pub content_type: String,
     pub language: Option<String>,
-    pub is_encrypted: i8,
+    pub is_encrypted: i16,
+    /// 不随 Clip 返回给客户端，分享链接的访问者也看不到
+    #[serde(skip_serializing)]
     pub encryption_key: Option<String>,
     pub access_type: String,
     pub view_count: i32,
@@ -69,6 +135,75 @@ pub struct ClipContent {
     pub tags: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub version: i32,
+    /// 查看次数上限，达到后 Clip 被彻底删除
+    pub max_views: Option<i32>,
+    /// 完整的分享链接，不存储在数据库中
+    #[sqlx(skip)]
+    pub share_url: Option<String>,
+    /// 分享链接二维码（SVG data URI），只在创建时返回
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qr_code: Option<String>,
+}
+
+#[cfg(test)]
+impl ClipContent {
+    /// 单元测试用的 Clip：用户 1 的私有纯文本，带短链接，按需用结构体更新语法覆盖字段
+    pub fn fixture() -> Self {
+        let now = Utc::now();
+        ClipContent {
+            id: 1,
+            user_id: 1,
+            title: None,
+            content: "content".to_string(),
+            content_type: "text".to_string(),
+            language: None,
+            is_encrypted: 0,
+            encryption_key: None,
+            access_type: "private".to_string(),
+            view_count: 0,
+            expires_at: None,
+            short_url: Some("abcd1234".to_string()),
+            tags: None,
+            created_at: now,
+            updated_at: now,
+            version: 1,
+            max_views: None,
+            share_url: None,
+            qr_code: None,
+        }
+    }
+}
+
+// 有查看次数上限的 Clip 在访问者确认前返回的预览，不含正文，也不计入查看次数
+#[derive(Debug, Serialize)]
+pub struct SharePreview {
+    pub title: Option<String>,
+    pub content_type: String,
+    pub language: Option<String>,
+    pub max_views: Option<i32>,
+    /// 剩余可查看次数
+    pub remaining_views: i32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub share_url: Option<String>,
+    /// 确认查看的地址，请求后才计入查看次数并返回正文
+    pub confirm_url: String,
+}
+
+impl SharePreview {
+    pub fn new(clip: &ClipContent, confirm_url: String) -> Self {
+        Self {
+            title: clip.title.clone(),
+            content_type: clip.content_type.clone(),
+            language: clip.language.clone(),
+            max_views: clip.max_views,
+            remaining_views: (clip.max_views.unwrap_or(0) - clip.view_count).max(0),
+            expires_at: clip.expires_at,
+            share_url: clip.share_url.clone(),
+            confirm_url,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -80,18 +215,209 @@ pub struct CreateClip {
     pub is_encrypted: Option<bool>,
     pub access_type: Option<String>,
     pub expires_at: Option<DateTime<Utc>>,
+    /// 相对有效期（如 `10m`、`1d`、`1w`），与 expires_at 二选一
+    pub expires_in: Option<String>,
     pub tags: Option<Vec<String>>,
+    pub max_views: Option<i32>,
+    /// 阅后即焚，等同于 max_views 为 1
+    pub burn_after_read: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Clip 修改（RFC 7396 JSON Merge Patch）
+///
+/// 字段缺省表示不修改；可清空的字段为 `Option<Option<T>>`，显式 null 即 `Some(None)`，表示清空；
+/// content 等不可为空的字段传 null 会被拒绝。
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UpdateClip {
-    pub title: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub title: Option<Option<String>>,
+    #[serde(default, deserialize_with = "non_null")]
     pub content: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub content_type: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub language: Option<Option<String>>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub is_encrypted: Option<bool>,
+    #[serde(default, deserialize_with = "non_null")]
+    pub access_type: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub expires_at: Option<Option<DateTime<Utc>>>,
+    /// 相对有效期，由业务层换算为 expires_at
+    #[serde(default, deserialize_with = "non_null")]
+    pub expires_in: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tags: Option<Option<Vec<String>>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub max_views: Option<Option<i32>>,
+    /// 阅后即焚，由业务层换算为 max_views
+    #[serde(default, deserialize_with = "non_null")]
+    pub burn_after_read: Option<bool>,
+}
+
+/// PUT 整体替换：未提供的可选字段清空，类型与访问权限恢复默认值
+impl From<CreateClip> for UpdateClip {
+    fn from(clip: CreateClip) -> Self {
+        Self {
+            title: Some(clip.title),
+            content: Some(clip.content),
+            content_type: Some(clip.content_type.unwrap_or_else(|| "text".to_string())),
+            language: Some(clip.language),
+            is_encrypted: Some(clip.is_encrypted.unwrap_or(false)),
+            access_type: Some(clip.access_type.unwrap_or_else(|| "private".to_string())),
+            // 只给出 expires_in 时由业务层换算，不能当作清空 expires_at
+            expires_at: (clip.expires_at.is_some() || clip.expires_in.is_none()).then_some(clip.expires_at),
+            expires_in: clip.expires_in,
+            tags: Some(clip.tags),
+            max_views: Some(clip.max_views),
+            burn_after_read: clip.burn_after_read,
+        }
+    }
+}
+
+/// Merge Patch 可清空字段：出现即为 Some，值为 null 时是 Some(None)
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Merge Patch 不可清空字段：值为 null 时反序列化失败
+fn non_null<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+// Clip 列表筛选与排序（查询参数，日期为 RFC 3339 格式）
+#[derive(Debug, Default, Deserialize)]
+pub struct ClipFilter {
     pub content_type: Option<String>,
     pub language: Option<String>,
     pub access_type: Option<String>,
-    pub expires_at: Option<DateTime<Utc>>,
-    pub tags: Option<Vec<String>>,
+    pub tag: Option<String>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    pub updated_from: Option<DateTime<Utc>>,
+    pub updated_to: Option<DateTime<Utc>>,
+    /// 默认只看未过期的，true 只看已过期（尚未被定时任务删除）的
+    #[serde(default)]
+    pub expired: bool,
+    #[serde(default)]
+    pub sort: ClipSort,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipSort {
+    #[default]
+    Created,
+    Updated,
+    ViewCount,
+    Title,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+// Clip 分页结果
+#[derive(Debug, Serialize)]
+pub struct ClipPage {
+    pub items: Vec<ClipContent>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+    pub has_more: bool,
+}
+
+// 回收站中的 Clip
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct TrashedClip {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub clip: ClipContent,
+    pub deleted_at: DateTime<Utc>,
+}
+
+// 增量同步的变更记录：deleted_at 非空表示已删除
+#[derive(Debug, sqlx::FromRow)]
+pub struct ClipChange {
+    #[sqlx(flatten)]
+    pub clip: ClipContent,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+// 已删除 Clip 的墓碑记录
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ClipTombstone {
+    pub id: i64,
+    pub deleted_at: DateTime<Utc>,
+}
+
+// 增量同步结果，cursor 用于下一次请求的 since 参数
+#[derive(Debug, Serialize)]
+pub struct SyncResponse {
+    pub clips: Vec<ClipContent>,
+    pub deleted: Vec<ClipTombstone>,
+    pub cursor: String,
+    pub has_more: bool,
+}
+
+// Clip 历史版本快照
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ClipRevision {
+    pub clip_id: i64,
+    pub version: i32,
+    pub title: Option<String>,
+    pub content: String,
+    pub tags: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+// 历史版本列表项（不含内容）
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ClipRevisionSummary {
+    pub clip_id: i64,
+    pub version: i32,
+    pub title: Option<String>,
+    pub tags: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+// 两个历史版本内容的差异（unified diff）
+#[derive(Debug, Serialize)]
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    pub diff: String,
+}
+
+// 搜索结果：高亮片段为转义后的 HTML，命中部分用 <mark> 包裹
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub clip: ClipContent,
+    pub score: f64,
+    pub title_highlight: Option<String>,
+    pub snippet: String,
+}
+
+// 搜索响应
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    pub items: Vec<SearchResult>,
+    /// 命中数超过候选上限：只有最近更新的一部分参与了相关度排序，更早的命中可能未出现，应换用更具体的关键词
+    pub truncated: bool,
 }
 
 // JWT Token
@@ -100,6 +426,15 @@ pub struct TokenClaims {
     pub sub: i64,
     pub exp: usize,
     pub iat: usize,
+    /// 令牌类型：access 或 refresh
+    #[serde(default)]
+    pub typ: String,
+    /// 令牌唯一ID，保证同一秒内签发的令牌也互不相同
+    #[serde(default)]
+    pub jti: String,
+    /// refresh token 所属的令牌族
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fam: Option<String>,
 }
 
 // API 响应模型
@@ -134,4 +469,50 @@ pub struct LoginResponse {
     pub access_token: String,
     pub refresh_token: String,
     pub expires_in: i64,
-}
\ No newline at end of file
+}
+
+/// 刷新后签发的新令牌
+#[derive(Debug, Serialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+// 安全审计事件
+#[derive(Debug)]
+pub struct NewAuditEvent {
+    pub user_id: Option<i64>,
+    /// 事件类型，如 password_changed
+    pub event: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub detail: Option<serde_json::Value>,
+}
+
+// 定时任务运行状态
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub interval_seconds: i64,
+    pub running: bool,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_finished_at: Option<DateTime<Utc>>,
+    /// 最近一次执行删除的记录数
+    pub last_affected: Option<u64>,
+    pub last_error: Option<String>,
+    /// 最近一次因锁由其他实例持有而跳过的时间
+    pub last_skipped_at: Option<DateTime<Utc>>,
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+// 登记一次登录尝试的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttemptOutcome {
+    /// 计数键处于锁定中，本次尝试未计数
+    Locked(DateTime<Utc>),
+    /// 已计数：窗口内连续失败次数（含本次）及因此设置的锁定到期时间
+    Counted {
+        failures: i32,
+        locked_until: Option<DateTime<Utc>>,
+    },
+}
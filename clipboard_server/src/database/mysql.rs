@@ -0,0 +1,1087 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{
+    mysql::{MySqlConnection, MySqlPool, MySqlPoolOptions},
+    MySql, QueryBuilder,
+};
+use std::time::Duration;
+
+use super::{
+    clip_order_by, generate_short_url, hash_password, like_pattern, migrate, tags_to_json,
+    models::*, AuditRepository, ClipRepository, JobRepository, LoginAttemptRepository, SessionRepository, Storage, UserRepository,
+};
+
+/// MySQL 存储后端
+pub struct MySqlStorage {
+    pool: MySqlPool,
+}
+
+impl MySqlStorage {
+    /// 初始化 MySQL 连接池
+    pub async fn connect(database_url: &str) -> Result<Self, sqlx::Error> {
+        let pool = MySqlPoolOptions::new()
+            .max_connections(10)
+            .acquire_timeout(Duration::from_secs(30))
+            .connect(database_url)
+            .await?;
+
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl Storage for MySqlStorage {
+    fn users(&self) -> &dyn UserRepository {
+        self
+    }
+
+    fn sessions(&self) -> &dyn SessionRepository {
+        self
+    }
+
+    fn clips(&self) -> &dyn ClipRepository {
+        self
+    }
+
+    fn jobs(&self) -> &dyn JobRepository {
+        self
+    }
+
+    fn audit(&self) -> &dyn AuditRepository {
+        self
+    }
+
+    fn login_attempts(&self) -> &dyn LoginAttemptRepository {
+        self
+    }
+
+    async fn migrate(&self, apply: bool) -> Result<i64, sqlx::Error> {
+        migrate::run("MySQL", &migrate::MYSQL, &self.pool, apply).await
+    }
+
+    async fn health_check(&self) -> Result<(), sqlx::Error> {
+        let _: (i32,) = sqlx::query_as("SELECT 1")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UserRepository for MySqlStorage {
+    async fn create_user(&self, user_data: &CreateUser) -> Result<User, sqlx::Error> {
+        let (password_hash, salt) = hash_password(&user_data.password)?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO clip_users (username, email, password_hash, salt)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+            .bind(&user_data.username)
+            .bind(&user_data.email)
+            .bind(password_hash)
+            .bind(salt)
+            .execute(&self.pool)
+            .await?;
+
+        UserRepository::find_by_id(self, result.last_insert_id() as i64).await
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<User, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, username, email, password_hash, salt, status,
+                   last_login_at, last_login_ip, login_count, created_at, updated_at
+            FROM clip_users
+            WHERE id = ? AND deleted_at IS NULL
+            "#,
+        )
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<User, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, username, email, password_hash, salt, status,
+                   last_login_at, last_login_ip, login_count, created_at, updated_at
+            FROM clip_users
+            WHERE username = ? AND deleted_at IS NULL
+            "#,
+        )
+            .bind(username)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn update_login_info(&self, user_id: i64, ip: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE clip_users
+            SET last_login_at = NOW(), last_login_ip = ?, login_count = login_count + 1
+            WHERE id = ?
+            "#,
+        )
+            .bind(ip)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_password(&self, user_id: i64, password: &str) -> Result<(), sqlx::Error> {
+        let (password_hash, salt) = hash_password(password)?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE clip_users SET password_hash = ?, salt = ?
+            WHERE id = ? AND deleted_at IS NULL
+            "#,
+        )
+            .bind(password_hash)
+            .bind(salt)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    async fn find_settings(&self, user_id: i64) -> Result<UserSettings, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT revision_retention, default_expires_in FROM clip_users WHERE id = ? AND deleted_at IS NULL
+            "#,
+        )
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn update_settings(&self, user_id: i64, settings: &UserSettings) -> Result<UserSettings, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE clip_users SET revision_retention = ?, default_expires_in = ? WHERE id = ?
+            "#,
+        )
+            .bind(settings.revision_retention)
+            .bind(&settings.default_expires_in)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM clip_revisions
+            WHERE user_id = ? AND version < (
+                SELECT c.version FROM clip_contents c WHERE c.id = clip_revisions.clip_id
+            ) - ?
+            "#,
+        )
+            .bind(user_id)
+            .bind(settings.revision_retention)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        self.find_settings(user_id).await
+    }
+}
+
+#[async_trait]
+impl SessionRepository for MySqlStorage {
+    async fn create_session(&self, session: &NewSession) -> Result<UserSession, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO clip_user_sessions (user_id, token, refresh_token, family_id, expires_at, refresh_expires_at, ip_address, device_info)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+            .bind(session.user_id)
+            .bind(&session.token)
+            .bind(&session.refresh_token)
+            .bind(&session.family_id)
+            .bind(session.expires_at)
+            .bind(session.refresh_expires_at)
+            .bind(&session.ip_address)
+            .bind(&session.device_info)
+            .execute(&self.pool)
+            .await?;
+
+        SessionRepository::find_by_id(self, result.last_insert_id() as i64).await
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<UserSession, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, user_id, token, refresh_token, family_id, expires_at, refresh_expires_at, device_info, ip_address, last_seen_at, created_at
+            FROM clip_user_sessions
+            WHERE id = ?
+            "#,
+        )
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn find_by_token(&self, token: &str) -> Result<UserSession, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, user_id, token, refresh_token, family_id, expires_at, refresh_expires_at, device_info, ip_address, last_seen_at, created_at
+            FROM clip_user_sessions
+            WHERE token = ? AND expires_at > NOW()
+            "#,
+        )
+            .bind(token)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn find_by_refresh_token(&self, refresh_token: &str) -> Result<UserSession, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, user_id, token, refresh_token, family_id, expires_at, refresh_expires_at, device_info, ip_address, last_seen_at, created_at
+            FROM clip_user_sessions
+            WHERE refresh_token = ? AND refresh_expires_at > NOW()
+            "#,
+        )
+            .bind(refresh_token)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn rotate_session(&self, id: i64, old_refresh_token: &str, tokens: &SessionTokens) -> Result<(), sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE clip_user_sessions
+            SET token = ?, refresh_token = ?, expires_at = ?, refresh_expires_at = ?
+            WHERE id = ? AND refresh_token = ?
+            "#,
+        )
+            .bind(&tokens.token)
+            .bind(&tokens.refresh_token)
+            .bind(tokens.expires_at)
+            .bind(tokens.refresh_expires_at)
+            .bind(id)
+            .bind(old_refresh_token)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    async fn delete_family(&self, family_id: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM clip_user_sessions WHERE family_id = ?
+            "#,
+        )
+            .bind(family_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_session(&self, token: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM clip_user_sessions WHERE token = ?
+            "#,
+        )
+            .bind(token)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find_by_user_id(&self, user_id: i64) -> Result<Vec<UserSession>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, user_id, token, refresh_token, family_id, expires_at, refresh_expires_at, device_info, ip_address, last_seen_at, created_at
+            FROM clip_user_sessions
+            WHERE user_id = ? AND refresh_expires_at > NOW()
+            ORDER BY COALESCE(last_seen_at, created_at) DESC, id DESC
+            "#,
+        )
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn delete_user_session(&self, user_id: i64, id: i64) -> Result<(), sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM clip_user_sessions WHERE id = ? AND user_id = ?
+            "#,
+        )
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    async fn delete_other_sessions(&self, user_id: i64, keep_id: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM clip_user_sessions WHERE user_id = ? AND id <> ?
+            "#,
+        )
+            .bind(user_id)
+            .bind(keep_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn touch_session(&self, id: i64, seen_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE clip_user_sessions SET last_seen_at = ? WHERE id = ?
+            "#,
+        )
+            .bind(seen_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_expired_sessions(&self, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM clip_user_sessions WHERE refresh_expires_at < ?
+            "#,
+        )
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl ClipRepository for MySqlStorage {
+    async fn create_clip(&self, user_id: i64, clip_data: &CreateClip) -> Result<ClipContent, sqlx::Error> {
+        let short_url = generate_short_url();
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO clip_contents (user_id, title, content, content_type, language, is_encrypted, access_type, expires_at, max_views, short_url, tags)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+            .bind(user_id)
+            .bind(&clip_data.title)
+            .bind(&clip_data.content)
+            .bind(clip_data.content_type.as_deref().unwrap_or("text"))
+            .bind(&clip_data.language)
+            .bind(clip_data.is_encrypted.unwrap_or(false) as i16)
+            .bind(clip_data.access_type.as_deref().unwrap_or("private"))
+            .bind(clip_data.expires_at)
+            .bind(clip_data.max_views)
+            .bind(short_url)
+            .bind(tags_to_json(&clip_data.tags))
+            .execute(&mut *tx)
+            .await?;
+
+        let id = result.last_insert_id() as i64;
+        save_revision(&mut tx, id).await?;
+        tx.commit().await?;
+
+        ClipRepository::find_by_id(self, id).await
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<ClipContent, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, user_id, title, content, content_type, language, is_encrypted, encryption_key,
+                   access_type, view_count, expires_at, short_url, tags, created_at, updated_at, version, max_views
+            FROM clip_contents
+            WHERE id = ? AND deleted_at IS NULL
+            "#,
+        )
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn find_by_short_url(&self, short_url: &str) -> Result<ClipContent, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, user_id, title, content, content_type, language, is_encrypted, encryption_key,
+                   access_type, view_count, expires_at, short_url, tags, created_at, updated_at, version, max_views
+            FROM clip_contents
+            WHERE short_url = ? AND deleted_at IS NULL
+            "#,
+        )
+            .bind(short_url)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn find_by_user_id(&self, user_id: i64, filter: &ClipFilter, page: i64, page_size: i64) -> Result<Vec<ClipContent>, sqlx::Error> {
+        let offset = (page - 1) * page_size;
+
+        let mut query = QueryBuilder::<MySql>::new(
+            r#"
+            SELECT id, user_id, title, content, content_type, language, is_encrypted, encryption_key,
+                   access_type, view_count, expires_at, short_url, tags, created_at, updated_at, version, max_views
+            FROM clip_contents"#,
+        );
+        push_clip_filter(&mut query, user_id, filter);
+        query.push(clip_order_by(filter))
+            .push(" LIMIT ")
+            .push_bind(page_size)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        query.build_query_as()
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn count_by_user_id(&self, user_id: i64, filter: &ClipFilter) -> Result<i64, sqlx::Error> {
+        let mut query = QueryBuilder::<MySql>::new("SELECT COUNT(*) FROM clip_contents");
+        push_clip_filter(&mut query, user_id, filter);
+
+        query.build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn find_changes(
+        &self,
+        user_id: i64,
+        since: Option<(DateTime<Utc>, i64)>,
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<ClipChange>, sqlx::Error> {
+        let mut query = QueryBuilder::<MySql>::new(
+            r#"
+            SELECT id, user_id, title, content, content_type, language, is_encrypted, encryption_key,
+                   access_type, view_count, expires_at, short_url, tags, created_at, updated_at, version, max_views, deleted_at
+            FROM clip_contents
+            WHERE user_id = "#,
+        );
+        query.push_bind(user_id)
+            .push(" AND updated_at < ")
+            .push_bind(until);
+
+        match since {
+            Some((updated_at, id)) => {
+                query.push(" AND (updated_at > ")
+                    .push_bind(updated_at)
+                    .push(" OR (updated_at = ")
+                    .push_bind(updated_at)
+                    .push(" AND id > ")
+                    .push_bind(id)
+                    .push("))");
+            }
+            None => {
+                query.push(" AND deleted_at IS NULL");
+            }
+        }
+
+        query.push(" ORDER BY updated_at ASC, id ASC LIMIT ").push_bind(limit);
+
+        query.build_query_as()
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn find_tombstones(
+        &self,
+        user_id: i64,
+        since: (DateTime<Utc>, i64),
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<ClipTombstone>, sqlx::Error> {
+        let (deleted_at, clip_id) = since;
+
+        sqlx::query_as(
+            r#"
+            SELECT clip_id AS id, deleted_at
+            FROM clip_tombstones
+            WHERE user_id = ? AND deleted_at < ?
+              AND (deleted_at > ? OR (deleted_at = ? AND clip_id > ?))
+            ORDER BY deleted_at ASC, clip_id ASC
+            LIMIT ?
+            "#,
+        )
+            .bind(user_id)
+            .bind(until)
+            .bind(deleted_at)
+            .bind(deleted_at)
+            .bind(clip_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn delete_tombstones(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM clip_tombstones WHERE deleted_at < ?
+            "#,
+        )
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn search(&self, user_id: i64, terms: &[String], now: DateTime<Utc>, limit: i64) -> Result<Vec<ClipContent>, sqlx::Error> {
+        // 与其他后端一样按子串匹配，不使用全文索引，同一查询在各后端结果一致；
+        // %关键词% 无法使用索引，需要扫描该用户的全部 Clips；单个用户数据量通常不大，内容很多时搜索耗时随之线性增长
+        let mut query = QueryBuilder::<MySql>::new(
+            r#"
+            SELECT id, user_id, title, content, content_type, language, is_encrypted, encryption_key,
+                   access_type, view_count, expires_at, short_url, tags, created_at, updated_at, version, max_views
+            FROM clip_contents
+            WHERE deleted_at IS NULL AND is_encrypted = 0 AND user_id = "#,
+        );
+        query.push_bind(user_id)
+            .push(" AND (expires_at IS NULL OR expires_at > ")
+            .push_bind(now)
+            .push(")");
+
+        // MySQL 的 LIKE 默认以反斜杠转义，且默认排序规则不区分大小写
+        for term in terms {
+            let pattern = like_pattern(term);
+            query.push(" AND (COALESCE(title, '') LIKE ")
+                .push_bind(pattern.clone())
+                .push(" OR content LIKE ")
+                .push_bind(pattern)
+                .push(")");
+        }
+
+        query.push(" ORDER BY updated_at DESC LIMIT ").push_bind(limit);
+
+        query.build_query_as()
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn update_clip(
+        &self,
+        id: i64,
+        user_id: i64,
+        clip_data: &UpdateClip,
+        expected_version: Option<i32>,
+    ) -> Result<ClipContent, sqlx::Error> {
+        let mut query = QueryBuilder::<MySql>::new("UPDATE clip_contents SET version = version + 1");
+        push_clip_patch(&mut query, clip_data);
+        query.push(" WHERE deleted_at IS NULL AND id = ")
+            .push_bind(id)
+            .push(" AND user_id = ")
+            .push_bind(user_id);
+        if let Some(version) = expected_version {
+            query.push(" AND version = ").push_bind(version);
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let result = query.build()
+            .execute(&mut *tx)
+            .await?;
+
+        // 不存在、无权访问或版本不匹配
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        save_revision(&mut tx, id).await?;
+        tx.commit().await?;
+
+        ClipRepository::find_by_id(self, id).await
+    }
+
+    async fn find_revisions(&self, clip_id: i64) -> Result<Vec<ClipRevisionSummary>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT clip_id, version, title, tags, created_at
+            FROM clip_revisions
+            WHERE clip_id = ?
+            ORDER BY version DESC
+            "#,
+        )
+            .bind(clip_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn find_revision(&self, clip_id: i64, version: i32) -> Result<ClipRevision, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT clip_id, version, title, content, tags, created_at
+            FROM clip_revisions
+            WHERE clip_id = ? AND version = ?
+            "#,
+        )
+            .bind(clip_id)
+            .bind(version)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn delete_clip(&self, id: i64, user_id: i64) -> Result<(), sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE clip_contents SET deleted_at = NOW() WHERE id = ? AND user_id = ? AND deleted_at IS NULL
+            "#,
+        )
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn find_trashed(&self, user_id: i64, page: i64, page_size: i64) -> Result<Vec<TrashedClip>, sqlx::Error> {
+        let offset = (page - 1) * page_size;
+
+        sqlx::query_as(
+            r#"
+            SELECT id, user_id, title, content, content_type, language, is_encrypted, encryption_key,
+                   access_type, view_count, expires_at, short_url, tags, created_at, updated_at, version, max_views, deleted_at
+            FROM clip_contents
+            WHERE user_id = ? AND deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+            .bind(user_id)
+            .bind(page_size)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn restore_clip(&self, id: i64, user_id: i64) -> Result<ClipContent, sqlx::Error> {
+        // 恢复时更新 updated_at，增量同步会把它作为修改重新下发
+        let result = sqlx::query(
+            r#"
+            UPDATE clip_contents SET deleted_at = NULL
+            WHERE id = ? AND user_id = ? AND deleted_at IS NOT NULL
+            "#,
+        )
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        ClipRepository::find_by_id(self, id).await
+    }
+
+    async fn purge_clip(&self, id: i64, user_id: i64) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let purged = purge_clips(&mut tx, |query| {
+            query.push("id = ")
+                .push_bind(id)
+                .push(" AND user_id = ")
+                .push_bind(user_id)
+                .push(" AND deleted_at IS NOT NULL");
+        }).await?;
+
+        if purged == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn empty_trash(&self, user_id: i64) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let purged = purge_clips(&mut tx, |query| {
+            query.push("user_id = ")
+                .push_bind(user_id)
+                .push(" AND deleted_at IS NOT NULL");
+        }).await?;
+
+        tx.commit().await?;
+        Ok(purged)
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let purged = purge_clips(&mut tx, |query| {
+            query.push("deleted_at IS NOT NULL AND deleted_at < ")
+                .push_bind(before);
+        }).await?;
+
+        tx.commit().await?;
+        Ok(purged)
+    }
+
+    async fn delete_expired_clips(&self, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let purged = purge_clips(&mut tx, |query| {
+            query.push("expires_at IS NOT NULL AND expires_at <= ")
+                .push_bind(now);
+        }).await?;
+
+        tx.commit().await?;
+        Ok(purged)
+    }
+
+    async fn delete_access_logs(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM clip_access_logs WHERE accessed_at < ?
+            "#,
+        )
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn view_clip(&self, id: i64) -> Result<ClipContent, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        // 条件更新会锁住该行，并发的读者只有一个能拿到最后一次查看；
+        // 查看不算修改，显式保留 updated_at，避免 ON UPDATE CURRENT_TIMESTAMP 刷新它
+        let result = sqlx::query(
+            r#"
+            UPDATE clip_contents SET view_count = view_count + 1, updated_at = updated_at
+            WHERE id = ? AND deleted_at IS NULL AND (max_views IS NULL OR view_count < max_views)
+            "#,
+        )
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        let clip: ClipContent = sqlx::query_as(
+            r#"
+            SELECT id, user_id, title, content, content_type, language, is_encrypted, encryption_key,
+                   access_type, view_count, expires_at, short_url, tags, created_at, updated_at, version, max_views
+            FROM clip_contents
+            WHERE id = ?
+            "#,
+        )
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if clip.max_views.is_some_and(|max_views| clip.view_count >= max_views) {
+            purge_clips(&mut tx, |query| {
+                query.push("id = ").push_bind(id);
+            }).await?;
+        }
+
+        tx.commit().await?;
+        Ok(clip)
+    }
+}
+
+#[async_trait]
+impl AuditRepository for MySqlStorage {
+    async fn record_event(&self, event: &NewAuditEvent) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO clip_audit_logs (user_id, event, ip_address, user_agent, detail)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+            .bind(event.user_id)
+            .bind(&event.event)
+            .bind(&event.ip_address)
+            .bind(&event.user_agent)
+            .bind(&event.detail)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for MySqlStorage {
+    async fn register_attempt(
+        &self,
+        key: &str,
+        now: DateTime<Utc>,
+        window_start: DateTime<Utc>,
+        lock_until: &(dyn Fn(i32) -> Option<DateTime<Utc>> + Sync),
+    ) -> Result<AttemptOutcome, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT IGNORE INTO clip_login_attempts (attempt_key, failures, last_failed_at) VALUES (?, 0, ?)
+            "#,
+        )
+            .bind(key)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        let (failures, last_failed_at, locked_until): (i32, DateTime<Utc>, Option<DateTime<Utc>>) = sqlx::query_as(
+            r#"
+            SELECT failures, last_failed_at, locked_until FROM clip_login_attempts WHERE attempt_key = ? FOR UPDATE
+            "#,
+        )
+            .bind(key)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if let Some(locked_until) = locked_until.filter(|until| *until > now) {
+            tx.commit().await?;
+            return Ok(AttemptOutcome::Locked(locked_until));
+        }
+
+        let failures = if last_failed_at < window_start { 1 } else { failures + 1 };
+        let locked_until = lock_until(failures);
+
+        sqlx::query(
+            r#"
+            UPDATE clip_login_attempts SET failures = ?, last_failed_at = ?, locked_until = ? WHERE attempt_key = ?
+            "#,
+        )
+            .bind(failures)
+            .bind(now)
+            .bind(locked_until)
+            .bind(key)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(AttemptOutcome::Counted { failures, locked_until })
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM clip_login_attempts WHERE attempt_key = ?
+            "#,
+        )
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn undo_attempt(&self, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE clip_login_attempts SET failures = failures - 1 WHERE attempt_key = ? AND failures > 0
+            "#,
+        )
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_stale_attempts(&self, before: DateTime<Utc>, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM clip_login_attempts
+            WHERE last_failed_at < ? AND (locked_until IS NULL OR locked_until < ?)
+            "#,
+        )
+            .bind(before)
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl JobRepository for MySqlStorage {
+    async fn try_lock(&self, name: &str, owner: &str, until: DateTime<Utc>) -> Result<bool, sqlx::Error> {
+        let now = Utc::now();
+
+        // 首次运行时先插入一条已到期的锁记录，之后统一走条件更新，由数据库保证只有一个实例更新成功
+        sqlx::query(
+            r#"
+            INSERT INTO clip_job_locks (name, owner, locked_until) VALUES (?, '', ?)
+            ON DUPLICATE KEY UPDATE name = name
+            "#,
+        )
+            .bind(name)
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE clip_job_locks SET owner = ?, locked_until = ?
+            WHERE name = ? AND (owner = ? OR locked_until <= ?)
+            "#,
+        )
+            .bind(owner)
+            .bind(until)
+            .bind(name)
+            .bind(owner)
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// 彻底删除满足条件的 Clips 并写入墓碑，供增量同步下发；历史版本与访问日志通过外键级联删除
+///
+/// 在同一事务中先写墓碑再删除，INSERT ... SELECT 会锁住选中的行，两条语句处理的是同一批记录。
+async fn purge_clips(
+    conn: &mut MySqlConnection,
+    condition: impl Fn(&mut QueryBuilder<'_, MySql>),
+) -> Result<u64, sqlx::Error> {
+    let mut insert = QueryBuilder::<MySql>::new(
+        "INSERT INTO clip_tombstones (clip_id, user_id, deleted_at) SELECT id, user_id, NOW() FROM clip_contents WHERE ",
+    );
+    condition(&mut insert);
+    // MySQL 5.7 重启后可能复用被删除的最大自增 ID，同一 ID 再次删除时覆盖旧墓碑
+    insert.push(" ON DUPLICATE KEY UPDATE user_id = VALUES(user_id), deleted_at = VALUES(deleted_at)");
+    insert.build()
+        .execute(&mut *conn)
+        .await?;
+
+    let mut delete = QueryBuilder::<MySql>::new("DELETE FROM clip_contents WHERE ");
+    condition(&mut delete);
+    let result = delete.build()
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// 将 Clip 的当前内容写入历史版本表，并按用户设置清理超出保留数量的旧版本
+async fn save_revision(conn: &mut MySqlConnection, clip_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO clip_revisions (clip_id, user_id, version, title, content, tags, created_at)
+        SELECT id, user_id, version, title, content, tags, updated_at FROM clip_contents WHERE id = ?
+        "#,
+    )
+        .bind(clip_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM clip_revisions
+        WHERE clip_id = ? AND version < (
+            SELECT c.version - u.revision_retention
+            FROM clip_contents c JOIN clip_users u ON u.id = c.user_id
+            WHERE c.id = ?
+        )
+        "#,
+    )
+        .bind(clip_id)
+        .bind(clip_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// 拼接 Merge Patch 的 SET 子句：只更新请求中出现的字段
+fn push_clip_patch(query: &mut QueryBuilder<'_, MySql>, patch: &UpdateClip) {
+    if let Some(title) = &patch.title {
+        query.push(", title = ").push_bind(title.clone());
+    }
+    if let Some(content) = &patch.content {
+        query.push(", content = ").push_bind(content.clone());
+    }
+    if let Some(content_type) = &patch.content_type {
+        query.push(", content_type = ").push_bind(content_type.clone());
+    }
+    if let Some(language) = &patch.language {
+        query.push(", language = ").push_bind(language.clone());
+    }
+    if let Some(is_encrypted) = patch.is_encrypted {
+        query.push(", is_encrypted = ").push_bind(is_encrypted as i16);
+    }
+    if let Some(access_type) = &patch.access_type {
+        query.push(", access_type = ").push_bind(access_type.clone());
+    }
+    if let Some(expires_at) = patch.expires_at {
+        query.push(", expires_at = ").push_bind(expires_at);
+    }
+    if let Some(tags) = &patch.tags {
+        query.push(", tags = ").push_bind(tags_to_json(tags));
+    }
+    if let Some(max_views) = patch.max_views {
+        query.push(", max_views = ").push_bind(max_views);
+    }
+}
+
+/// 拼接 Clip 列表的 WHERE 条件（只含用户自己的、未删除的 Clips）
+fn push_clip_filter(query: &mut QueryBuilder<'_, MySql>, user_id: i64, filter: &ClipFilter) {
+    query.push(" WHERE deleted_at IS NULL AND user_id = ").push_bind(user_id);
+
+    if let Some(content_type) = &filter.content_type {
+        query.push(" AND content_type = ").push_bind(content_type.clone());
+    }
+    if let Some(language) = &filter.language {
+        query.push(" AND language = ").push_bind(language.clone());
+    }
+    if let Some(access_type) = &filter.access_type {
+        query.push(" AND access_type = ").push_bind(access_type.clone());
+    }
+    if let Some(tag) = &filter.tag {
+        query.push(" AND JSON_CONTAINS(tags, JSON_QUOTE(").push_bind(tag.clone()).push("))");
+    }
+    if let Some(created_from) = filter.created_from {
+        query.push(" AND created_at >= ").push_bind(created_from);
+    }
+    if let Some(created_to) = filter.created_to {
+        query.push(" AND created_at <= ").push_bind(created_to);
+    }
+    if let Some(updated_from) = filter.updated_from {
+        query.push(" AND updated_at >= ").push_bind(updated_from);
+    }
+    if let Some(updated_to) = filter.updated_to {
+        query.push(" AND updated_at <= ").push_bind(updated_to);
+    }
+    if filter.expired {
+        query.push(" AND expires_at IS NOT NULL AND expires_at <= NOW()");
+    } else {
+        query.push(" AND (expires_at IS NULL OR expires_at > NOW())");
+    }
+}
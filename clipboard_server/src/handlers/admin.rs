@@ -0,0 +1,19 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    database::models::ApiResponse,
+    services::{Scheduler, ServiceError},
+};
+
+/// 获取定时任务的运行状态
+pub async fn get_jobs(
+    State(scheduler): State<Scheduler>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let response = ApiResponse::success(scheduler.statuses(), "获取定时任务状态成功");
+    Ok((StatusCode::OK, Json(response)))
+}
@@ -1,186 +1,58 @@
 use axum::{
-    extract::{State, Json},
+    extract::{Extension, Path, State, Json},
     http::{StatusCode, HeaderMap},
     response::IntoResponse,
 };
-use chrono::{Utc, Duration};
-use jsonwebtoken::{encode, EncodingKey, Header, Algorithm};
 use serde_json::json;
-use std::net::SocketAddr;
 
 use crate::{
-    database::{models::{ApiResponse, CreateUser, LoginUser, LoginResponse, TokenClaims}, UserRepository, SessionRepository, DbPool},
     config::Config,
+    database::models::{ApiResponse, ChangePassword, CreateUser, LoginUser},
+    middlewares::ClientIp,
+    services::{AuthService, ServiceError},
 };
 
 /// 用户注册
 pub async fn register(
-    State(pool): State<DbPool>,
+    State(auth_service): State<AuthService>,
     Json(user_data): Json<CreateUser>,
-) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
-    // 检查用户名是否已存在
-    if UserRepository::find_by_username(&pool, &user_data.username.as_str()).await.is_ok() {
-        return Err((StatusCode::BAD_REQUEST, Json(ApiResponse::error("用户名已存在"))));
-    }
-
-    // 创建用户
-    match UserRepository::create_user(&pool, &user_data).await {
-        Ok(user) => {
-            let response = ApiResponse::success(user, "用户注册成功");
-            Ok((StatusCode::CREATED, Json(response)))
-        }
-        Err(e) => {
-            tracing::error!("用户注册失败: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error("用户注册失败"))))
-        }
-    }
+) -> Result<impl IntoResponse, ServiceError> {
+    let user = auth_service.register(user_data).await?;
+
+    let response = ApiResponse::success(user, "用户注册成功");
+    Ok((StatusCode::CREATED, Json(response)))
 }
 
 /// 用户登录
 pub async fn login(
-    State(pool): State<DbPool>,
-    State(config): State<Config>,
-    addr: Option<axum::extract::ConnectInfo<SocketAddr>>,
+    State(auth_service): State<AuthService>,
+    ClientIp(ip): ClientIp,
     headers: HeaderMap,
     Json(login_data): Json<LoginUser>,
-) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
-    let ip = addr.map(|ci| ci.0.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
-    let device_info = headers.get("user-agent")
-        .and_then(|ua| ua.to_str().ok())
-        .map(|s| s.to_string());
-
-    // 验证用户密码
-    let user = match UserRepository::verify_password(&pool, &login_data.username, &login_data.password).await {
-        Ok(user) => user,
-        Err(_) => {
-            return Err((StatusCode::UNAUTHORIZED, Json(ApiResponse::error("用户名或密码错误"))));
-        }
-    };
-
-    // 检查用户状态
-    if user.status == 0 {
-        return Err((StatusCode::FORBIDDEN, Json(ApiResponse::error("用户已被禁用"))));
-    }
-
-    // 生成 JWT token
-    let now = Utc::now();
-    let expires_at = now + Duration::seconds(config.jwt_expires_in);
-    let refresh_expires_at = now + Duration::seconds(config.jwt_refresh_expires_in);
-
-    let claims = TokenClaims {
-        sub: user.id,
-        exp: expires_at.timestamp() as usize,
-        iat: now.timestamp() as usize,
-    };
-
-    let token = encode(
-        &Header::new(Algorithm::HS256),
-        &claims,
-        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
-    ).map_err(|e| {
-        tracing::error!("JWT token 生成失败: {}", e);
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error("登录失败")))
-    })?;
-
-    let refresh_claims = TokenClaims {
-        sub: user.id,
-        exp: refresh_expires_at.timestamp() as usize,
-        iat: now.timestamp() as usize,
-    };
-
-    let refresh_token = encode(
-        &Header::new(Algorithm::HS256),
-        &refresh_claims,
-        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
-    ).map_err(|e| {
-        tracing::error!("Refresh token 生成失败: {}", e);
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error("登录失败")))
-    })?;
-
-    // 创建会话
-    match SessionRepository::create_session(
-        &pool,
-        user.id,
-        &token,
-        &refresh_token,
-        expires_at,
-        refresh_expires_at,
-        &ip,
-        device_info,
-    ).await {
-        Ok(_) => {
-            // 更新用户登录信息
-            if let Err(e) = UserRepository::update_login_info(&pool, user.id, &ip).await {
-                tracing::error!("更新用户登录信息失败: {}", e);
-            }
-
-            let login_response = LoginResponse {
-                user,
-                access_token: token,
-                refresh_token,
-                expires_in: config.jwt_expires_in,
-            };
-
-            let response = ApiResponse::success(login_response, "登录成功");
-            Ok((StatusCode::OK, Json(response)))
-        }
-        Err(e) => {
-            tracing::error!("创建会话失败: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error("登录失败"))))
-        }
-    }
+) -> Result<impl IntoResponse, ServiceError> {
+    let device_info = user_agent(&headers);
+
+    let login_response = auth_service.login(&login_data, &ip, device_info).await?;
+
+    let response = ApiResponse::success(login_response, "登录成功");
+    Ok((StatusCode::OK, Json(response)))
 }
 
-/// 刷新 token
+/// 刷新 token：请求头携带 refresh token，返回轮换后的 access token 与 refresh token
 pub async fn refresh_token(
-    State(pool): State<DbPool>,
+    State(auth_service): State<AuthService>,
     State(config): State<Config>,
     headers: HeaderMap,
-) -> Result<impl IntoResponse, (StatusCode, Json<ApiResponse<()>>)> {
-    let auth_header = headers.get("authorization")
-        .and_then(|header| header.to_str().ok());
-
-    let refresh_token = if let Some(header) = auth_header {
-        if header.starts_with("Bearer ") {
-            &header[7..]
-        } else {
-            return Err((StatusCode::UNAUTHORIZED, Json(ApiResponse::error("无效的 token 格式"))));
-        }
-    } else {
-        return Err((StatusCode::UNAUTHORIZED, Json(ApiResponse::error("缺少认证头"))));
-    };
-
-    // 验证 refresh token
-    let session = match SessionRepository::find_by_token(&pool, refresh_token).await {
-        Ok(session) => session,
-        Err(_) => {
-            return Err((StatusCode::UNAUTHORIZED, Json(ApiResponse::error("无效的 refresh token"))));
-        }
-    };
-
-    // 生成新的 access token
-    let now = Utc::now();
-    let expires_at = now + Duration::seconds(config.jwt_expires_in);
-
-    let claims = TokenClaims {
-        sub: session.user_id,
-        exp: expires_at.timestamp() as usize,
-        iat: now.timestamp() as usize,
-    };
-
-    let new_token = encode(
-        &Header::new(Algorithm::HS256),
-        &claims,
-        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
-    ).map_err(|e| {
-        tracing::error!("JWT token 生成失败: {}", e);
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error("token 刷新失败")))
-    })?;
+) -> Result<impl IntoResponse, ServiceError> {
+    let refresh_token = bearer_token(&headers)?;
+
+    let tokens = auth_service.refresh(refresh_token).await?;
 
     let response = json!({
         "status": "success",
         "data": {
-            "access_token": new_token,
+            "access_token": tokens.access_token,
+            "refresh_token": tokens.refresh_token,
             "expires_in": config.jwt_expires_in,
             "token_type": "Bearer"
         },
@@ -192,55 +64,105 @@ pub async fn refresh_token(
 
 /// 退出登录
 pub async fn logout(
-    State(pool): State<DbPool>,
+    State(auth_service): State<AuthService>,
     headers: HeaderMap,
-) -> impl IntoResponse {
-    let auth_header = headers.get("authorization")
-        .and_then(|header| header.to_str().ok());
-
-    let token = if let Some(header) = auth_header {
-        if header.starts_with("Bearer ") {
-            &header[7..]
-        } else {
-            return (StatusCode::UNAUTHORIZED, Json(ApiResponse::<()>::error("无效的 token 格式")));
-        }
-    } else {
-        return (StatusCode::UNAUTHORIZED, Json(ApiResponse::<()>::error("缺少认证头")));
-    };
-
-    match SessionRepository::delete_session(&pool, token).await {
-        Ok(_) => (StatusCode::OK, Json(ApiResponse::success((), "退出登录成功"))),
-        Err(e) => {
-            tracing::error!("删除会话失败: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error("退出登录失败")))
-        }
-    }
+) -> Result<impl IntoResponse, ServiceError> {
+    let token = bearer_token(&headers)?;
+
+    auth_service.logout(token).await?;
+
+    Ok((StatusCode::OK, Json(ApiResponse::success((), "退出登录成功"))))
 }
 
 /// 获取当前用户信息
 pub async fn get_me(
-    user_id: i64,
-    State(pool): State<DbPool>,
-) -> impl IntoResponse {
-    match UserRepository::find_by_id(&pool, user_id).await {
-        Ok(user) => {
-            // 不返回密码等敏感信息
-            let user_response = json!({
-                "id": user.id,
-                "username": user.username,
-                "email": user.email,
-                "status": user.status,
-                "last_login_at": user.last_login_at,
-                "login_count": user.login_count,
-                "created_at": user.created_at
-            });
-
-            let response = ApiResponse::success(user_response, "获取用户信息成功");
-            (StatusCode::OK, Json(response))
-        }
-        Err(e) => {
-            tracing::error!("获取用户信息失败: {}", e);
-            (StatusCode::NOT_FOUND, Json(ApiResponse::error("用户不存在")))
-        }
-    }
-}
\ No newline at end of file
+    Extension(user_id): Extension<i64>,
+    State(auth_service): State<AuthService>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let user = auth_service.current_user(user_id).await?;
+
+    // 不返回密码等敏感信息
+    let user_response = json!({
+        "id": user.id,
+        "username": user.username,
+        "email": user.email,
+        "status": user.status,
+        "last_login_at": user.last_login_at,
+        "login_count": user.login_count,
+        "created_at": user.created_at
+    });
+
+    let response = ApiResponse::success(user_response, "获取用户信息成功");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 修改密码，成功后其他设备需重新登录
+pub async fn change_password(
+    State(auth_service): State<AuthService>,
+    Extension(user_id): Extension<i64>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+    Json(change): Json<ChangePassword>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let token = bearer_token(&headers)?;
+
+    let revoked = auth_service.change_password(user_id, token, &change, &ip, user_agent(&headers)).await?;
+
+    let response = ApiResponse::success(json!({ "revoked_sessions": revoked }), "密码修改成功，其他设备需重新登录");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 当前用户的登录会话（设备）列表
+pub async fn get_sessions(
+    State(auth_service): State<AuthService>,
+    Extension(user_id): Extension<i64>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ServiceError> {
+    let token = bearer_token(&headers)?;
+
+    let sessions = auth_service.sessions(user_id, token).await?;
+
+    let response = ApiResponse::success(sessions, "获取会话列表成功");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 注销指定会话
+pub async fn delete_session(
+    State(auth_service): State<AuthService>,
+    Path(id): Path<i64>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    auth_service.revoke_session(user_id, id).await?;
+
+    Ok((StatusCode::OK, Json(ApiResponse::success((), "会话已注销"))))
+}
+
+/// 退出其他所有设备，只保留当前会话
+pub async fn delete_other_sessions(
+    State(auth_service): State<AuthService>,
+    Extension(user_id): Extension<i64>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ServiceError> {
+    let token = bearer_token(&headers)?;
+
+    let revoked = auth_service.revoke_other_sessions(user_id, token).await?;
+
+    let response = ApiResponse::success(json!({ "revoked": revoked }), "已退出其他设备");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers.get("user-agent")
+        .and_then(|ua| ua.to_str().ok())
+        .map(|s| s.to_string())
+}
+
+/// 从 Authorization 头中取出 Bearer token
+fn bearer_token(headers: &HeaderMap) -> Result<&str, ServiceError> {
+    let header = headers.get("authorization")
+        .and_then(|header| header.to_str().ok())
+        .ok_or_else(|| ServiceError::unauthorized("缺少认证头"))?;
+
+    header.strip_prefix("Bearer ")
+        .ok_or_else(|| ServiceError::unauthorized("无效的 token 格式"))
+}
@@ -1,16 +1,16 @@
 use axum::{
-    extract::{State, Path, Query},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Extension, OriginalUri, State, Path, Query},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{Html, IntoResponse, Response},
     Json,
 };
+use chrono::Utc;
+use qrcode::EcLevel;
 use serde::Deserialize;
 
 use crate::{
-    database::{
-        models::{ApiResponse, CreateClip, UpdateClip},
-        ClipRepository, DbPool
-    },
+    database::models::{ApiResponse, ClipContent, ClipFilter, ClipPage, CreateClip, SharePreview, UpdateClip},
+    services::{page, qr, raw, ClipService, ServiceError},
 };
 
 #[derive(Debug, Deserialize)]
@@ -19,132 +19,438 @@ pub struct Pagination {
     pub page_size: Option<i64>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    pub from: i32,
+    pub to: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShareQuery {
+    /// 确认查看有查看次数上限的 Clip
+    pub confirm: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QrQuery {
+    /// 边长（像素）
+    pub size: Option<u32>,
+    /// 纠错等级 L / M / Q / H
+    pub ec: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncQuery {
+    pub since: Option<String>,
+    pub limit: Option<i64>,
+}
+
 /// 创建 Clip
 pub async fn create_clip(
-    State(pool): State<DbPool>,
+    State(clip_service): State<ClipService>,
+    Extension(user_id): Extension<i64>,
     Json(clip_data): Json<CreateClip>,
-    user_id: i64,
-) -> impl IntoResponse {
-    match ClipRepository::create_clip(&pool, user_id, &clip_data).await {
-        Ok(clip) => {
-            let response = ApiResponse::success(clip, "Clip 创建成功");
-            (StatusCode::CREATED, Json(response))
-        }
-        Err(e) => {
-            tracing::error!("创建 Clip 失败: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error("创建 Clip 失败")))
-        }
-    }
+) -> Result<impl IntoResponse, ServiceError> {
+    let clip = clip_service.create(user_id, clip_data).await?;
+
+    let headers = etag_header(&clip);
+    let response = ApiResponse::success(clip, "Clip 创建成功");
+    Ok((StatusCode::CREATED, headers, Json(response)))
 }
 
-/// 获取用户的 Clips
+/// 获取用户的 Clips（支持筛选、排序，分页链接见 Link 响应头）
 pub async fn get_user_clips(
-    State(pool): State<DbPool>,
+    State(clip_service): State<ClipService>,
+    OriginalUri(uri): OriginalUri,
     Query(pagination): Query<Pagination>,
-    user_id: i64,
-) -> impl IntoResponse {
+    Query(filter): Query<ClipFilter>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
     let page = pagination.page.unwrap_or(1);
     let page_size = pagination.page_size.unwrap_or(20);
 
-    if page < 1 || page_size < 1 || page_size > 100 {
-        return (StatusCode::BAD_REQUEST, Json(ApiResponse::error("分页参数无效")));
-    }
+    let clips = clip_service.list(user_id, &filter, page, page_size).await?;
 
-    match ClipRepository::find_by_user_id(&pool, user_id, page, page_size).await {
-        Ok(clips) => {
-            let response = ApiResponse::success(clips, "获取 Clips 成功");
-            (StatusCode::OK, Json(response))
-        }
-        Err(e) => {
-            tracing::error!("获取用户 Clips 失败: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error("获取 Clips 失败")))
-        }
+    let mut headers = HeaderMap::new();
+    if let Ok(link) = HeaderValue::from_str(&pagination_links(uri.path(), uri.query(), &clips)) {
+        headers.insert(header::LINK, link);
     }
+
+    let response = ApiResponse::success(clips, "获取 Clips 成功");
+    Ok((StatusCode::OK, headers, Json(response)))
+}
+
+/// 全文搜索当前用户的 Clips
+pub async fn search_clips(
+    State(clip_service): State<ClipService>,
+    Query(query): Query<SearchQuery>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let limit = query.limit.unwrap_or(20);
+
+    let results = clip_service.search(user_id, &query.q, limit).await?;
+
+    let response = ApiResponse::success(results, "搜索成功");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 增量同步：返回 since 游标之后的变更与删除记录
+pub async fn sync_clips(
+    State(clip_service): State<ClipService>,
+    Query(query): Query<SyncQuery>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let limit = query.limit.unwrap_or(100);
+    let since = query.since.as_deref().filter(|since| !since.is_empty());
+
+    let changes = clip_service.sync(user_id, since, limit).await?;
+
+    let response = ApiResponse::success(changes, "同步成功");
+    Ok((StatusCode::OK, Json(response)))
 }
 
 /// 根据 ID 获取 Clip
 pub async fn get_clip_by_id(
-    State(pool): State<DbPool>,
+    State(clip_service): State<ClipService>,
     Path(id): Path<i64>,
-) -> impl IntoResponse {
-    match ClipRepository::find_by_id(&pool, id).await {
-        Ok(clip) => {
-            // 增加查看次数
-            if let Err(e) = ClipRepository::increment_view_count(&pool, id).await {
-                tracing::error!("增加查看次数失败: {}", e);
-            }
-
-            let response = ApiResponse::success(clip, "获取 Clip 成功");
-            (StatusCode::OK, Json(response))
-        }
-        Err(e) => {
-            tracing::error!("获取 Clip 失败: {}", e);
-            (StatusCode::NOT_FOUND, Json(ApiResponse::error("Clip 不存在")))
-        }
-    }
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let clip = clip_service.get(id, user_id).await?;
+
+    let headers = etag_header(&clip);
+    let response = ApiResponse::success(clip, "获取 Clip 成功");
+    Ok((StatusCode::OK, headers, Json(response)))
 }
 
-/// 根据短链接获取 Clip
+/// 分享链接 /s/:short_url：浏览器访问（Accept 含 text/html）时返回 HTML 分享页，否则返回 Clip 的 JSON
+///
+/// 无需登录，所有者登录后查看不计入查看次数。有查看次数上限的 Clip 需带 `?confirm=1` 才计数并返回正文，
+/// 否则 HTML 返回确认页、JSON 返回不含正文的预览，避免链接预览等自动抓取消耗查看次数。
 pub async fn get_clip_by_short_url(
-    State(pool): State<DbPool>,
+    State(clip_service): State<ClipService>,
     Path(short_url): Path<String>,
-) -> impl IntoResponse {
-    match ClipRepository::find_by_short_url(&pool, &short_url).await {
-        Ok(clip) => {
-            // 增加查看次数
-            if let Err(e) = ClipRepository::increment_view_count(&pool, clip.id).await {
-                tracing::error!("增加查看次数失败: {}", e);
-            }
-
-            let response = ApiResponse::success(clip, "获取 Clip 成功");
-            (StatusCode::OK, Json(response))
+    Query(query): Query<ShareQuery>,
+    viewer: Option<Extension<i64>>,
+    request_headers: HeaderMap,
+) -> Result<Response, ServiceError> {
+    let viewer = viewer.map(|Extension(user_id)| user_id);
+    let wants_html = request_headers.get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+
+    let needs_confirmation = |clip: &ClipContent| {
+        clip.max_views.is_some() && viewer != Some(clip.user_id) && query.confirm.is_none()
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::VARY, HeaderValue::from_static("Accept"));
+
+    if !wants_html {
+        let clip = clip_service.find_shared(&short_url, viewer).await?;
+        if needs_confirmation(&clip) {
+            headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+            let preview = SharePreview::new(&clip, format!("/s/{}?confirm=1", short_url));
+            let response = ApiResponse::success(preview, "该 Clip 有查看次数上限，确认查看请访问 confirm_url");
+            return Ok((StatusCode::OK, headers, Json(response)).into_response());
+        }
+
+        let clip = clip_service.view_shared(clip, viewer).await?;
+        let response = ApiResponse::success(clip, "获取 Clip 成功");
+        return Ok((StatusCode::OK, headers, Json(response)).into_response());
+    }
+
+    headers.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    headers.insert(header::REFERRER_POLICY, HeaderValue::from_static("no-referrer"));
+    let nonce = uuid::Uuid::new_v4().simple().to_string();
+    if let Ok(csp) = HeaderValue::from_str(&page::content_security_policy(&nonce)) {
+        headers.insert(header::CONTENT_SECURITY_POLICY, csp);
+    }
+
+    let clip = match clip_service.find_shared(&short_url, viewer).await {
+        // 有查看上限的 Clip 需访问者确认后才计数，链接预览只能看到确认页
+        Ok(clip) if needs_confirmation(&clip) => {
+            headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+            return Ok((StatusCode::OK, headers, Html(page::confirm_page(&clip))).into_response());
+        }
+        Ok(clip) => clip_service.view_shared(clip, viewer).await,
+        Err(e) => Err(e),
+    };
+    let clip = match clip {
+        Ok(clip) => clip,
+        Err(ServiceError::NotFound(message)) => {
+            return Ok((StatusCode::NOT_FOUND, headers, Html(page::error_page(&message))).into_response());
         }
-        Err(e) => {
-            tracing::error!("获取 Clip 失败: {}", e);
-            (StatusCode::NOT_FOUND, Json(ApiResponse::error("Clip 不存在或已过期")))
+        Err(ServiceError::Gone(message)) => {
+            return Ok((StatusCode::GONE, headers, Html(page::error_page(&message))).into_response());
         }
+        Err(e) => return Err(e),
+    };
+
+    if let Ok(cache_control) = HeaderValue::from_str(&raw::cache_control(&clip, Utc::now())) {
+        headers.insert(header::CACHE_CONTROL, cache_control);
+    }
+    let html = page::share_page(&clip, &format!("/r/{}", short_url), &nonce);
+    Ok((StatusCode::OK, headers, Html(html)).into_response())
+}
+
+/// 分享链接 /r/:short_url：只返回 Clip 正文，供 curl、wget 和脚本使用
+///
+/// Content-Type 由 content_type / language 推断，Content-Disposition 给出文件名，
+/// Cache-Control 不超过 Clip 的过期时间。
+pub async fn get_raw_clip(
+    State(clip_service): State<ClipService>,
+    Path(short_url): Path<String>,
+    viewer: Option<Extension<i64>>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let viewer = viewer.map(|Extension(user_id)| user_id);
+    let clip = clip_service.get_by_short_url(&short_url, viewer).await?;
+
+    let mut headers = etag_header(&clip);
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(raw::media_type(&clip)));
+    headers.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    if let Ok(disposition) = HeaderValue::from_str(&raw::content_disposition(&clip)) {
+        headers.insert(header::CONTENT_DISPOSITION, disposition);
+    }
+    if let Ok(cache_control) = HeaderValue::from_str(&raw::cache_control(&clip, Utc::now())) {
+        headers.insert(header::CACHE_CONTROL, cache_control);
     }
+
+    Ok((StatusCode::OK, headers, clip.content))
+}
+
+/// 分享链接的二维码（SVG），/s/:short_url/qr.svg
+pub async fn get_qr_svg(
+    State(clip_service): State<ClipService>,
+    Path(short_url): Path<String>,
+    Query(query): Query<QrQuery>,
+    viewer: Option<Extension<i64>>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let (share_url, size, ec_level, cache_control) = qr_params(&clip_service, &short_url, &query, viewer).await?;
+
+    let svg = qr::svg(&share_url, size, ec_level)
+        .map_err(|e| ServiceError::internal("生成二维码失败", e))?;
+
+    Ok((StatusCode::OK, qr_headers("image/svg+xml", &cache_control), svg))
+}
+
+/// 分享链接的二维码（PNG），/s/:short_url/qr.png
+pub async fn get_qr_png(
+    State(clip_service): State<ClipService>,
+    Path(short_url): Path<String>,
+    Query(query): Query<QrQuery>,
+    viewer: Option<Extension<i64>>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let (share_url, size, ec_level, cache_control) = qr_params(&clip_service, &short_url, &query, viewer).await?;
+
+    let png = qr::png(&share_url, size, ec_level)
+        .map_err(|e| ServiceError::internal("生成二维码失败", e))?;
+
+    Ok((StatusCode::OK, qr_headers("image/png", &cache_control), png))
 }
 
-/// 更新 Clip
+/// 整体替换 Clip（PUT），请求体与创建时相同，未提供的可选字段会被清空
+///
+/// 并发控制：请求带 `If-Match: "<version>"`（取自 GET 响应的 ETag）时，只有服务端版本一致才会更新，
+/// 否则返回 409 及服务端当前数据。不带 If-Match 或 `If-Match: *` 时按最后写入为准（需求 §2.3）。
+/// If-Match 只支持单个强 ETag，多个 ETag 或弱 ETag 返回 412。
 pub async fn update_clip(
-    State(pool): State<DbPool>,
+    State(clip_service): State<ClipService>,
     Path(id): Path<i64>,
+    Extension(user_id): Extension<i64>,
+    request_headers: HeaderMap,
+    Json(clip_data): Json<CreateClip>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let expected_version = if_match_version(&request_headers)?;
+
+    let clip = clip_service.update(id, user_id, UpdateClip::from(clip_data), expected_version).await?;
+
+    let headers = etag_header(&clip);
+    let response = ApiResponse::success(clip, "Clip 更新成功");
+    Ok((StatusCode::OK, headers, Json(response)))
+}
+
+/// 部分修改 Clip（PATCH，RFC 7396 JSON Merge Patch），并发控制同 PUT
+pub async fn patch_clip(
+    State(clip_service): State<ClipService>,
+    Path(id): Path<i64>,
+    Extension(user_id): Extension<i64>,
+    request_headers: HeaderMap,
     Json(clip_data): Json<UpdateClip>,
-    user_id: i64,
-) -> impl IntoResponse {
-    match ClipRepository::update_clip(&pool, id, user_id, &clip_data).await {
-        Ok(clip) => {
-            let response = ApiResponse::success(clip, "Clip 更新成功");
-            (StatusCode::OK, Json(response))
-        }
-        Err(sqlx::Error::RowNotFound) => {
-            (StatusCode::NOT_FOUND, Json(ApiResponse::error("Clip 不存在或无权访问")))
-        }
-        Err(e) => {
-            tracing::error!("更新 Clip 失败: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error("更新 Clip 失败")))
-        }
-    }
+) -> Result<impl IntoResponse, ServiceError> {
+    let expected_version = if_match_version(&request_headers)?;
+
+    let clip = clip_service.update(id, user_id, clip_data, expected_version).await?;
+
+    let headers = etag_header(&clip);
+    let response = ApiResponse::success(clip, "Clip 更新成功");
+    Ok((StatusCode::OK, headers, Json(response)))
+}
+
+/// 获取 Clip 的历史版本列表
+pub async fn get_revisions(
+    State(clip_service): State<ClipService>,
+    Path(id): Path<i64>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let revisions = clip_service.revisions(id, user_id).await?;
+
+    let response = ApiResponse::success(revisions, "获取历史版本成功");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 获取 Clip 的指定历史版本
+pub async fn get_revision(
+    State(clip_service): State<ClipService>,
+    Path((id, version)): Path<(i64, i32)>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let revision = clip_service.revision(id, user_id, version).await?;
+
+    let response = ApiResponse::success(revision, "获取历史版本成功");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 比较两个历史版本（unified diff），to 缺省时与当前版本比较
+pub async fn diff_revisions(
+    State(clip_service): State<ClipService>,
+    Path(id): Path<i64>,
+    Query(query): Query<DiffQuery>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let diff = clip_service.diff_revisions(id, user_id, query.from, query.to).await?;
+
+    let response = ApiResponse::success(diff, "比较历史版本成功");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 恢复历史版本：以该版本的标题、内容、标签生成新版本，支持 If-Match
+pub async fn restore_revision(
+    State(clip_service): State<ClipService>,
+    Path((id, version)): Path<(i64, i32)>,
+    Extension(user_id): Extension<i64>,
+    request_headers: HeaderMap,
+) -> Result<impl IntoResponse, ServiceError> {
+    let expected_version = if_match_version(&request_headers)?;
+
+    let clip = clip_service.restore_revision(id, user_id, version, expected_version).await?;
+
+    let headers = etag_header(&clip);
+    let response = ApiResponse::success(clip, "历史版本已恢复");
+    Ok((StatusCode::OK, headers, Json(response)))
 }
 
 /// 删除 Clip
 pub async fn delete_clip(
-    State(pool): State<DbPool>,
+    State(clip_service): State<ClipService>,
     Path(id): Path<i64>,
-    user_id: i64,
-) -> impl IntoResponse {
-    match ClipRepository::delete_clip(&pool, id, user_id).await {
-        Ok(_) => {
-            let response = ApiResponse::success((), "Clip 删除成功");
-            (StatusCode::OK, Json(response))
-        }
-        Err(sqlx::Error::RowNotFound) => {
-            (StatusCode::NOT_FOUND, Json(ApiResponse::error("Clip 不存在或无权访问")))
-        }
-        Err(e) => {
-            tracing::error!("删除 Clip 失败: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error("删除 Clip 失败")))
-        }
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    clip_service.delete(id, user_id).await?;
+
+    let response = ApiResponse::success((), "Clip 删除成功");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 生成 RFC 8288 分页链接（first / prev / next / last），保留原有的筛选参数
+fn pagination_links(path: &str, query: Option<&str>, clips: &ClipPage) -> String {
+    let params: Vec<&str> = query.unwrap_or("")
+        .split('&')
+        .filter(|param| !param.is_empty() && !param.starts_with("page="))
+        .collect();
+    let link = |page: i64, rel: &str| {
+        let mut query = params.clone();
+        let page = format!("page={}", page);
+        query.push(&page);
+        format!("<{}?{}>; rel=\"{}\"", path, query.join("&"), rel)
+    };
+
+    let last_page = ((clips.total + clips.page_size - 1) / clips.page_size).max(1);
+    let mut links = vec![link(1, "first")];
+    if clips.page > 1 {
+        links.push(link((clips.page - 1).min(last_page), "prev"));
     }
-}
\ No newline at end of file
+    if clips.has_more {
+        links.push(link(clips.page + 1, "next"));
+    }
+    links.push(link(last_page, "last"));
+
+    links.join(", ")
+}
+
+/// 校验二维码参数，并确认访问者可以打开该分享链接（不计入查看次数）
+async fn qr_params(
+    clip_service: &ClipService,
+    short_url: &str,
+    query: &QrQuery,
+    viewer: Option<Extension<i64>>,
+) -> Result<(String, u32, EcLevel, String), ServiceError> {
+    let size = query.size.unwrap_or(qr::DEFAULT_SIZE);
+    if !(qr::MIN_SIZE..=qr::MAX_SIZE).contains(&size) {
+        return Err(ServiceError::bad_request(&format!("size 应在 {} 到 {} 之间", qr::MIN_SIZE, qr::MAX_SIZE)));
+    }
+    let ec_level = qr::parse_ec_level(query.ec.as_deref())
+        .ok_or_else(|| ServiceError::bad_request("ec 应为 L、M、Q、H 之一"))?;
+
+    let viewer = viewer.map(|Extension(user_id)| user_id);
+    let clip = clip_service.find_shared(short_url, viewer).await?;
+    let cache_control = qr::cache_control(&clip, Utc::now());
+    let share_url = clip.share_url
+        .ok_or_else(|| ServiceError::not_found("Clip 不存在"))?;
+
+    Ok((share_url, size, ec_level, cache_control))
+}
+
+fn qr_headers(content_type: &'static str, cache_control: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if let Ok(cache_control) = HeaderValue::from_str(cache_control) {
+        headers.insert(header::CACHE_CONTROL, cache_control);
+    }
+    headers
+}
+
+/// Clip 的 ETag，取值为版本号
+fn etag_header(clip: &ClipContent) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Ok(etag) = HeaderValue::from_str(&format!("\"{}\"", clip.version)) {
+        headers.insert(header::ETAG, etag);
+    }
+    headers
+}
+
+/// 解析 If-Match 中的版本号；缺省或为 `*` 时返回 None
+///
+/// 只支持单个强 ETag。弱 ETag 按 RFC 9110 的强比较永远不匹配；多个 ETag 的列表无法作为单个版本号
+/// 做原子比较。这两种情况以及不是版本号的 ETag 都返回 412，格式不合法时返回 400。
+fn if_match_version(headers: &HeaderMap) -> Result<Option<i32>, ServiceError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+
+    let invalid = || ServiceError::bad_request("无效的 If-Match 头");
+    let value = value.to_str().map_err(|_| invalid())?.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+
+    let is_entity_tag = |tag: &str| {
+        let tag = tag.trim();
+        let tag = tag.strip_prefix("W/").unwrap_or(tag);
+        tag.len() >= 2 && tag.starts_with('"') && tag.ends_with('"')
+    };
+    if !value.split(',').all(is_entity_tag) {
+        return Err(invalid());
+    }
+
+    value.strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .and_then(|v| v.parse().ok())
+        .map(Some)
+        .ok_or_else(|| ServiceError::precondition_failed("If-Match 只支持单个强 ETag"))
+}
@@ -1,5 +1,8 @@
+pub mod admin;
 pub mod auth;
 pub mod clips;
+pub mod trash;
+pub mod users;
 
 use axum::{
     extract::State,
@@ -25,7 +28,7 @@ pub async fn health_check(
             Ok(Json(serde_json::json!({
                 "status": "success",
                 "data": health_status,
-                "message": "服务端正常运行 + MySQL 连接成功 ✅"
+                "message": "服务端正常运行 + 数据库连接成功 ✅"
             })))
         }
         Err(e) => {
@@ -44,7 +47,7 @@ pub async fn health_check(
 /// 根路径欢迎接口
 pub async fn root() -> Json<Value> {
     Json(serde_json::json!({
-        "message": "欢迎使用 Axum + SQLx MySQL 服务",
+        "message": "欢迎使用 Axum + SQLx 服务",
         "version": "1.0.0"
     }))
 }
\ No newline at end of file
@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Extension, State, Path, Query},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use super::clips::Pagination;
+use crate::{
+    database::models::ApiResponse,
+    services::{ClipService, ServiceError},
+};
+
+/// 获取回收站中的 Clips（按删除时间倒序）
+pub async fn get_trash(
+    State(clip_service): State<ClipService>,
+    Query(pagination): Query<Pagination>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let page = pagination.page.unwrap_or(1);
+    let page_size = pagination.page_size.unwrap_or(20);
+
+    let clips = clip_service.trash(user_id, page, page_size).await?;
+
+    let response = ApiResponse::success(clips, "获取回收站成功");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 从回收站恢复 Clip
+pub async fn restore_clip(
+    State(clip_service): State<ClipService>,
+    Path(id): Path<i64>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let clip = clip_service.restore(id, user_id).await?;
+
+    let response = ApiResponse::success(clip, "Clip 已恢复");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 彻底删除回收站中的 Clip，历史版本一并删除
+pub async fn purge_clip(
+    State(clip_service): State<ClipService>,
+    Path(id): Path<i64>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    clip_service.purge(id, user_id).await?;
+
+    let response = ApiResponse::success((), "Clip 已彻底删除");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 清空回收站
+pub async fn empty_trash(
+    State(clip_service): State<ClipService>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let deleted = clip_service.empty_trash(user_id).await?;
+
+    let response = ApiResponse::success(json!({ "deleted": deleted }), "回收站已清空");
+    Ok((StatusCode::OK, Json(response)))
+}
@@ -0,0 +1,34 @@
+use axum::{
+    extract::{Extension, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    database::models::{ApiResponse, UpdateUserSettings},
+    services::{ServiceError, UserService},
+};
+
+/// 获取当前用户的设置
+pub async fn get_settings(
+    State(user_service): State<UserService>,
+    Extension(user_id): Extension<i64>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let settings = user_service.settings(user_id).await?;
+
+    let response = ApiResponse::success(settings, "获取用户设置成功");
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// 修改当前用户的设置
+pub async fn update_settings(
+    State(user_service): State<UserService>,
+    Extension(user_id): Extension<i64>,
+    Json(changes): Json<UpdateUserSettings>,
+) -> Result<impl IntoResponse, ServiceError> {
+    let settings = user_service.update_settings(user_id, &changes).await?;
+
+    let response = ApiResponse::success(settings, "用户设置已保存");
+    Ok((StatusCode::OK, Json(response)))
+}
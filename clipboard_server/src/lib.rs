@@ -0,0 +1,80 @@
+pub mod config;
+pub mod database;
+pub mod handlers;
+pub mod middlewares;
+pub mod services;
+pub mod state;
+
+use axum::{
+    routing::get,
+    Router,
+    middleware,
+};
+
+use state::AppState;
+
+/// 组装所有路由与中间件
+pub fn app(state: AppState) -> Router {
+    // 需要认证的路由；限流放在认证之后，按用户计数（按 IP 的限流在最外层，先于认证）
+    let auth_routes = Router::new()
+        .route("/api/auth/me", get(handlers::auth::get_me))
+        .route("/api/auth/logout", axum::routing::post(handlers::auth::logout))
+        .route("/api/auth/password", axum::routing::post(handlers::auth::change_password))
+        .route("/api/auth/sessions", get(handlers::auth::get_sessions))
+        .route("/api/auth/sessions", axum::routing::delete(handlers::auth::delete_other_sessions))
+        .route("/api/auth/sessions/:id", axum::routing::delete(handlers::auth::delete_session))
+        .route("/api/clips", axum::routing::post(handlers::clips::create_clip))
+        .route("/api/clips", get(handlers::clips::get_user_clips))
+        .route("/api/clips/search", get(handlers::clips::search_clips))
+        .route("/api/sync", get(handlers::clips::sync_clips))
+        .route("/api/clips/:id", get(handlers::clips::get_clip_by_id))
+        .route("/api/clips/:id", axum::routing::put(handlers::clips::update_clip))
+        .route("/api/clips/:id", axum::routing::patch(handlers::clips::patch_clip))
+        .route("/api/clips/:id", axum::routing::delete(handlers::clips::delete_clip))
+        .route("/api/clips/:id/revisions", get(handlers::clips::get_revisions))
+        .route("/api/clips/:id/revisions/diff", get(handlers::clips::diff_revisions))
+        .route("/api/clips/:id/revisions/:version", get(handlers::clips::get_revision))
+        .route("/api/clips/:id/revisions/:version/restore", axum::routing::post(handlers::clips::restore_revision))
+        .route("/api/trash", get(handlers::trash::get_trash))
+        .route("/api/trash", axum::routing::delete(handlers::trash::empty_trash))
+        .route("/api/trash/:id", axum::routing::delete(handlers::trash::purge_clip))
+        .route("/api/trash/:id/restore", axum::routing::post(handlers::trash::restore_clip))
+        .route("/api/users/me/settings", get(handlers::users::get_settings))
+        .route("/api/users/me/settings", axum::routing::put(handlers::users::update_settings))
+        .layer(middleware::from_fn_with_state(state.clone(), middlewares::rate_limit))
+        .layer(middleware::from_fn_with_state(state.clone(), middlewares::auth));
+
+    // 管理员路由：先认证，再校验管理员身份
+    let admin_routes = Router::new()
+        .route("/api/admin/jobs", get(handlers::admin::get_jobs))
+        .layer(middleware::from_fn_with_state(state.clone(), middlewares::rate_limit))
+        .layer(middleware::from_fn_with_state(state.clone(), middlewares::admin))
+        .layer(middleware::from_fn_with_state(state.clone(), middlewares::auth));
+
+    // 公开路由
+    let public_routes = Router::new()
+        .route("/", get(handlers::root))
+        .route("/health", get(handlers::health_check))
+        .route("/api/auth/register", axum::routing::post(handlers::auth::register))
+        .route("/api/auth/login", axum::routing::post(handlers::auth::login))
+        .route("/api/auth/refresh", axum::routing::post(handlers::auth::refresh_token))
+        .layer(middleware::from_fn_with_state(state.clone(), middlewares::rate_limit));
+
+    // 分享链接：无需登录，带有效令牌时识别所有者
+    let share_routes = Router::new()
+        .route("/s/:short_url", get(handlers::clips::get_clip_by_short_url))
+        .route("/s/:short_url/qr.svg", get(handlers::clips::get_qr_svg))
+        .route("/s/:short_url/qr.png", get(handlers::clips::get_qr_png))
+        .route("/r/:short_url", get(handlers::clips::get_raw_clip))
+        .route_layer(middleware::from_fn_with_state(state.clone(), middlewares::rate_limit))
+        .route_layer(middleware::from_fn_with_state(state.clone(), middlewares::optional_auth));
+
+    Router::new()
+        .merge(public_routes)
+        .merge(share_routes)
+        .merge(auth_routes)
+        .merge(admin_routes)
+        .layer(middleware::from_fn_with_state(state.clone(), middlewares::ip_rate_limit))
+        .layer(tower_http::cors::CorsLayer::permissive())
+        .with_state(state)
+}
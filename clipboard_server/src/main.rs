@@ -1,21 +1,7 @@
-mod config;
-mod database;
-mod handlers;
-mod middlewares;
-
-use axum::{
-    routing::get,
-    Router,
-    middleware,
-    extract::Request,
-    http::{StatusCode, HeaderMap},
-};
+use axum_sqlx_app::{config::Config, database, state::AppState};
 use std::net::SocketAddr;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use config::Config;
-use database::DbPool;
-
 #[tokio::main]
 async fn main() {
     // 初始化日志
@@ -29,44 +15,19 @@ async fn main() {
     tracing::info!("配置加载成功 ✅");
 
     // 初始化数据库连接池
+    let backend = config.database_backend.name();
     let pool = database::init_pool(&config)
         .await
-        .expect("无法创建 MySQL 连接池，请检查：1. 数据库地址/密码正确 2. MySQL 服务已启动 3. 数据库已创建");
-    tracing::info!("MySQL 连接池初始化成功 ✅");
+        .unwrap_or_else(|e| panic!("无法初始化 {} 数据库，请检查：1. 数据库地址/密码正确 2. 数据库服务已启动 3. 数据库结构版本与程序匹配（{}）", backend, e));
+    tracing::info!("{} 连接池初始化成功 ✅", backend);
 
     // 创建共享状态
-    let shared_state = (pool.clone(), config.clone());
-
-    // 需要认证的路由
-    let auth_routes = Router::new()
-        .route("/api/auth/me", get(handlers::auth::get_me))
-        .route("/api/auth/logout", axum::routing::post(handlers::auth::logout))
-        .route("/api/clips", axum::routing::post(handlers::clips::create_clip))
-        .route("/api/clips", get(handlers::clips::get_user_clips))
-        .route("/api/clips/:id", get(handlers::clips::get_clip_by_id))
-        .route("/api/clips/:id", axum::routing::put(handlers::clips::update_clip))
-        .route("/api/clips/:id", axum::routing::delete(handlers::clips::delete_clip))
-        .layer(middleware::from_fn_with_state(
-            shared_state.clone(),
-            |state: axum::extract::State<(DbPool, Config)>, request: Request, next: middleware::Next| async move {
-                let headers = request.headers().clone();
-                middlewares::auth(state, headers, request, next).await
-            }
-        ));
+    let shared_state = AppState::new(pool, config.clone());
 
-    // 公开路由
-    let public_routes = Router::new()
-        .route("/", get(handlers::root))
-        .route("/health", get(handlers::health_check))
-        .route("/api/clips/:short_url", get(handlers::clips::get_clip_by_short_url))
-        .route("/api/auth/register", axum::routing::post(handlers::auth::register))
-        .route("/api/auth/login", axum::routing::post(handlers::auth::login))
-        .route("/api/auth/refresh", axum::routing::post(handlers::auth::refresh_token));
+    // 启动定时任务：清理过期 Clips、回收站、过期会话、访问日志、登录失败计数与删除墓碑
+    shared_state.scheduler.start();
 
-    let app = Router::new()
-        .merge(public_routes)
-        .merge(auth_routes)
-        .layer(tower_http::cors::CorsLayer::permissive());
+    let app = axum_sqlx_app::app(shared_state);
 
     // 启动服务
     let addr = SocketAddr::from(([0, 0, 0, 0], config.server_port));
@@ -76,7 +37,8 @@ async fn main() {
         .await
         .expect("无法绑定端口，请检查端口是否被占用");
 
-    axum::serve(listener, app)
-
+    // 记录连接信息，供登录、审计等获取客户端 IP
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
         .expect("服务端启动失败");
-}
\ No newline at end of file
+}
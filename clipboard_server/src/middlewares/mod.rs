@@ -1,72 +1,143 @@
+use async_trait::async_trait;
 use axum::{
-    extract::{Request, State},
+    extract::{ConnectInfo, Extension, FromRequestParts, Request, State},
+    http::{request::Parts, HeaderMap},
     middleware::Next,
-    response::Response,
-    http::StatusCode,
+    response::{IntoResponse, Response},
 };
-use jsonwebtoken::{decode, DecodingKey, Validation};
-use serde_json::json;
+use std::{convert::Infallible, net::SocketAddr};
 
 use crate::{
-    database::{models::TokenClaims, SessionRepository, DbPool},
-    config::Config,
+    services::{
+        client_ip,
+        rate_limit::{RouteGroup, Subject},
+        AuthService, RateLimiter, ServiceError,
+    },
+    state::AppState,
 };
 
+/// 客户端 IP，取不到连接信息时为 "unknown"；经过受信任的代理时取转发头中的地址（见 TRUSTED_PROXIES）
+pub struct ClientIp(pub String);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let peer = parts.extensions.get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip());
+        let ip = client_ip::resolve(peer, &parts.headers, &state.config.trusted_proxies);
+
+        Ok(ClientIp(ip.map(|ip| ip.to_string()).unwrap_or_else(|| "unknown".to_string())))
+    }
+}
+
 /// 认证中间件
 pub async fn auth(
-    State((pool, config)): State<(DbPool, Config)>,
-    headers: axum::http::HeaderMap,
+    State(auth_service): State<AuthService>,
+    headers: HeaderMap,
     mut request: Request,
     next: Next,
-) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
+) -> Result<Response, ServiceError> {
     let token = extract_token_from_header(&headers)
-        .ok_or_else(|| (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(json!({
-                "status": "error",
-                "message": "缺少认证令牌"
-            }))
-        ))?;
-
-    // 验证 token 是否在会话表中
-    if SessionRepository::find_by_token(&pool, &token).await.is_err() {
-        return Err((
-            StatusCode::UNAUTHORIZED,
-            axum::Json(json!({
-                "status": "error",
-                "message": "令牌已过期或无效"
-            }))
-        ));
-    }
+        .ok_or_else(|| ServiceError::unauthorized("缺少认证令牌"))?;
 
-    // 验证 JWT token
-    let claims = decode::<TokenClaims>(
-        &token,
-        &DecodingKey::from_secret(config.jwt_secret.as_bytes()),
-        &Validation::default(),
-    )
-        .map_err(|_| (
-            StatusCode::UNAUTHORIZED,
-            axum::Json(json!({
-            "status": "error",
-            "message": "无效的令牌"
-        }))
-        ))?
-        .claims;
+    let user_id = auth_service.authenticate(&token).await?;
 
     // 将用户ID添加到请求扩展中
-    request.extensions_mut().insert(claims.sub);
+    request.extensions_mut().insert(user_id);
+
+    Ok(next.run(request).await)
+}
+
+/// 可选认证中间件：带有效令牌时写入用户ID，没有或无效时按匿名访问继续处理
+pub async fn optional_auth(
+    State(auth_service): State<AuthService>,
+    headers: HeaderMap,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(token) = extract_token_from_header(&headers) {
+        if let Ok(user_id) = auth_service.authenticate(&token).await {
+            request.extensions_mut().insert(user_id);
+        }
+    }
+
+    next.run(request).await
+}
+
+/// 管理员中间件，需放在认证中间件之后
+pub async fn admin(
+    State(auth_service): State<AuthService>,
+    Extension(user_id): Extension<i64>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ServiceError> {
+    auth_service.ensure_admin(user_id).await?;
 
     Ok(next.run(request).await)
 }
 
+/// 限流中间件：已登录按用户、匿名按 IP 计数，并写入 RateLimit-* 响应头
+///
+/// 需放在认证中间件之后（内层）才能识别用户。
+pub async fn rate_limit(
+    State(rate_limiter): State<RateLimiter>,
+    ClientIp(ip): ClientIp,
+    request: Request,
+    next: Next,
+) -> Response {
+    let subject = match request.extensions().get::<i64>() {
+        Some(user_id) => Subject::User(*user_id),
+        None => Subject::Ip(ip),
+    };
+    let group = RouteGroup::classify(request.method(), request.uri().path());
+
+    let Some(decision) = rate_limiter.check(&subject, group) else {
+        return next.run(request).await;
+    };
+
+    let mut response = match decision.retry_after {
+        Some(retry_after) => ServiceError::too_many_requests("请求过于频繁，请稍后再试", retry_after).into_response(),
+        None => next.run(request).await,
+    };
+    decision.write_headers(response.headers_mut());
+
+    response
+}
+
+/// IP 限流中间件：在认证之前按客户端 IP 计数，匿名请求无法借认证失败绕过限流
+///
+/// 放在最外层；内层限流已写入更宽松的 RateLimit-* 响应头时才覆盖。
+pub async fn ip_rate_limit(
+    State(rate_limiter): State<RateLimiter>,
+    ClientIp(ip): ClientIp,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(decision) = rate_limiter.check_ip(&ip) else {
+        return next.run(request).await;
+    };
+
+    if let Some(retry_after) = decision.retry_after {
+        let mut response = ServiceError::too_many_requests("请求过于频繁，请稍后再试", retry_after).into_response();
+        decision.write_headers(response.headers_mut());
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    let inner_remaining = response.headers().get("ratelimit-remaining")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u32>().ok());
+    if inner_remaining.is_none_or(|remaining| decision.remaining < remaining) {
+        decision.write_headers(response.headers_mut());
+    }
+
+    response
+}
+
 /// 从请求头中提取 token
-fn extract_token_from_header(headers: &axum::http::HeaderMap) -> Option<String> {
+fn extract_token_from_header(headers: &HeaderMap) -> Option<String> {
     let auth_header = headers.get("authorization")?.to_str().ok()?;
 
-    if auth_header.starts_with("Bearer ") {
-        Some(auth_header[7..].to_string())
-    } else {
-        None
-    }
-}
\ No newline at end of file
+    auth_header.strip_prefix("Bearer ").map(|token| token.to_string())
+}
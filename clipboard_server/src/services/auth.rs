@@ -0,0 +1,441 @@
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use uuid::Uuid;
+
+use super::{device, password, ServiceError};
+use crate::{
+    config::Config,
+    database::{
+        models::{AttemptOutcome, ChangePassword, CreateUser, LoginResponse, LoginUser, NewAuditEvent, NewSession, SessionInfo, SessionTokens, TokenClaims, TokenPair, User},
+        DbPool,
+    },
+};
+
+/// JWT 中 typ 的取值
+const TOKEN_TYPE_ACCESS: &str = "access";
+const TOKEN_TYPE_REFRESH: &str = "refresh";
+/// 会话活跃时间的最小更新间隔（秒）
+const LAST_SEEN_INTERVAL_SECONDS: i64 = 300;
+/// 连续登录失败的统计窗口（小时），超过窗口未再失败则重新计数
+pub const LOGIN_FAILURE_WINDOW_HOURS: i64 = 24;
+/// 单次锁定的最长秒数
+const LOGIN_MAX_LOCKOUT_SECONDS: i64 = 3600;
+
+/// 认证业务：注册、登录、令牌签发与校验
+#[derive(Clone)]
+pub struct AuthService {
+    pool: DbPool,
+    config: Config,
+}
+
+impl AuthService {
+    pub fn new(pool: DbPool, config: Config) -> Self {
+        Self { pool, config }
+    }
+
+    /// 用户注册，用户名去掉首尾空白并转为小写后保存
+    pub async fn register(&self, mut user_data: CreateUser) -> Result<User, ServiceError> {
+        password::check(&user_data.password)?;
+        user_data.username = normalize_username(&user_data.username);
+
+        // 检查用户名是否已存在
+        if self.pool.users().find_by_username(&user_data.username).await.is_ok() {
+            return Err(ServiceError::bad_request("用户名已存在"));
+        }
+
+        self.pool.users().create_user(&user_data).await
+            .map_err(|e| ServiceError::internal("用户注册失败", e))
+    }
+
+    /// 用户登录，成功后创建会话
+    ///
+    /// 账号和 IP 分别统计连续失败次数，达到上限后锁定并返回 429；
+    /// 每次尝试在验证密码之前先按失败登记，并发的尝试也无法越过上限。
+    /// 验证成功后清除账号的计数，IP 的计数只撤销本次登记：同一 IP 上对其他账号的失败仍然有效，
+    /// 不能借登录自己的账号重置。登记不区分用户名是否存在，避免借此探测账号。
+    pub async fn login(
+        &self,
+        login_data: &LoginUser,
+        ip: &str,
+        device_info: Option<String>,
+    ) -> Result<LoginResponse, ServiceError> {
+        let now = Utc::now();
+        let username = normalize_username(&login_data.username);
+        let ip_key = format!("ip:{}", ip);
+        let user_key = user_attempt_key(&username);
+        // 先登记 IP：IP 已被锁定时不再消耗账号的次数
+        let attempt_keys = [
+            (&ip_key, self.config.login_ip_max_failures),
+            (&user_key, self.config.login_max_failures),
+        ];
+
+        // 账号或 IP 处于锁定中时直接拒绝；本次失败会触发的锁定在验证失败后返回
+        let mut locked_until: Option<DateTime<Utc>> = None;
+        for (key, max_failures) in &attempt_keys {
+            match self.register_attempt(key, *max_failures, now, "登录失败").await? {
+                AttemptOutcome::Locked(until) => return Err(Self::login_locked(until, now)),
+                AttemptOutcome::Counted { locked_until: until, .. } => locked_until = locked_until.max(until),
+            }
+        }
+
+        // 验证用户密码
+        let user = match self.pool.users().verify_password(&username, &login_data.password).await {
+            Ok(user) => user,
+            Err(sqlx::Error::RowNotFound) => {
+                return Err(match locked_until {
+                    Some(locked_until) => Self::login_locked(locked_until, now),
+                    None => ServiceError::unauthorized("用户名或密码错误"),
+                });
+            }
+            Err(e) => return Err(ServiceError::internal("登录失败", format!("验证密码失败: {}", e))),
+        };
+
+        // 检查用户状态，被禁用的账号不清除失败计数
+        if user.status == 0 {
+            return Err(ServiceError::forbidden("用户已被禁用"));
+        }
+
+        // 登录成功，清除账号的失败计数，撤销本次对 IP 的登记
+        if let Err(e) = self.pool.login_attempts().clear(&user_key).await {
+            tracing::error!("清除登录失败计数失败: {}", e);
+        }
+        if let Err(e) = self.pool.login_attempts().undo_attempt(&ip_key).await {
+            tracing::error!("撤销 IP 登录计数失败: {}", e);
+        }
+
+        // 生成 JWT token，每次登录开启一个新的令牌族
+        let family_id = Uuid::new_v4().to_string();
+        let tokens = self.issue_tokens(user.id, &family_id)
+            .map_err(|e| ServiceError::internal("登录失败", format!("JWT token 生成失败: {}", e)))?;
+
+        // 创建会话
+        let new_session = NewSession {
+            user_id: user.id,
+            token: tokens.token.clone(),
+            refresh_token: tokens.refresh_token.clone(),
+            family_id,
+            expires_at: tokens.expires_at,
+            refresh_expires_at: tokens.refresh_expires_at,
+            ip_address: ip.to_string(),
+            device_info,
+        };
+
+        self.pool.sessions().create_session(&new_session).await
+            .map_err(|e| ServiceError::internal("登录失败", format!("创建会话失败: {}", e)))?;
+
+        // 更新用户登录信息
+        if let Err(e) = self.pool.users().update_login_info(user.id, ip).await {
+            tracing::error!("更新用户登录信息失败: {}", e);
+        }
+
+        Ok(LoginResponse {
+            user,
+            access_token: tokens.token,
+            refresh_token: tokens.refresh_token,
+            expires_in: self.config.jwt_expires_in,
+        })
+    }
+
+    /// 登记一次密码验证尝试（登录或修改密码）；达到上限的计数键按 2 的幂次延长锁定时间
+    async fn register_attempt(
+        &self,
+        key: &str,
+        max_failures: i32,
+        now: DateTime<Utc>,
+        context: &str,
+    ) -> Result<AttemptOutcome, ServiceError> {
+        let window_start = now - Duration::hours(LOGIN_FAILURE_WINDOW_HOURS);
+        let lockout_seconds = self.config.login_lockout_seconds;
+        let lock_until = move |failures: i32| {
+            (failures >= max_failures).then(|| {
+                let exponent = (failures - max_failures).clamp(0, 16) as u32;
+                now + Duration::seconds((lockout_seconds * 2_i64.pow(exponent)).min(LOGIN_MAX_LOCKOUT_SECONDS))
+            })
+        };
+
+        let outcome = self.pool.login_attempts().register_attempt(key, now, window_start, &lock_until).await
+            .map_err(|e| ServiceError::internal(context, format!("记录密码验证次数失败: {}", e)))?;
+
+        if let AttemptOutcome::Counted { failures, locked_until: Some(locked_until) } = outcome {
+            tracing::warn!("{} 连续验证密码 {} 次，锁定 {} 秒", key, failures, (locked_until - now).num_seconds());
+        }
+        Ok(outcome)
+    }
+
+    fn login_locked(locked_until: DateTime<Utc>, now: DateTime<Utc>) -> ServiceError {
+        let retry_after = (locked_until - now).num_seconds().max(1) as u64;
+        ServiceError::too_many_requests("密码错误次数过多，请稍后再试", retry_after)
+    }
+
+    /// 刷新 token：轮换 access token 与 refresh token，旧的 refresh token 随即失效
+    ///
+    /// 已被轮换过的 refresh token 再次出现，说明令牌可能已泄露，吊销整个令牌族（该次登录的会话）。
+    pub async fn refresh(&self, refresh_token: &str) -> Result<TokenPair, ServiceError> {
+        let claims = self.decode_token(refresh_token, TOKEN_TYPE_REFRESH)
+            .map_err(|_| ServiceError::unauthorized("无效的 refresh token"))?;
+
+        let session = match self.pool.sessions().find_by_refresh_token(refresh_token).await {
+            Ok(session) => session,
+            Err(sqlx::Error::RowNotFound) => {
+                self.revoke_family(claims.fam.as_deref()).await;
+                return Err(ServiceError::unauthorized("无效的 refresh token"));
+            }
+            Err(e) => return Err(ServiceError::internal("token 刷新失败", e)),
+        };
+
+        let tokens = self.issue_tokens(session.user_id, &session.family_id)
+            .map_err(|e| ServiceError::internal("token 刷新失败", format!("JWT token 生成失败: {}", e)))?;
+
+        // 条件更新：并发请求中只有一个能轮换成功，其余视为重复使用
+        match self.pool.sessions().rotate_session(session.id, refresh_token, &tokens).await {
+            Ok(()) => Ok(TokenPair {
+                access_token: tokens.token,
+                refresh_token: tokens.refresh_token,
+            }),
+            Err(sqlx::Error::RowNotFound) => {
+                self.revoke_family(Some(&session.family_id)).await;
+                Err(ServiceError::unauthorized("无效的 refresh token"))
+            }
+            Err(e) => Err(ServiceError::internal("token 刷新失败", e)),
+        }
+    }
+
+    /// 修改密码：验证当前密码后重新生成哈希，并注销当前会话以外的所有会话
+    ///
+    /// 当前密码验证与登录共用账号的失败计数，达到上限后同样锁定并返回 429。
+    pub async fn change_password(
+        &self,
+        user_id: i64,
+        current_token: &str,
+        change: &ChangePassword,
+        ip: &str,
+        user_agent: Option<String>,
+    ) -> Result<u64, ServiceError> {
+        let user = self.current_user(user_id).await?;
+
+        let now = Utc::now();
+        let attempt_key = user_attempt_key(&user.username);
+        let locked_until = match self.register_attempt(&attempt_key, self.config.login_max_failures, now, "修改密码失败").await? {
+            AttemptOutcome::Locked(until) => return Err(Self::login_locked(until, now)),
+            AttemptOutcome::Counted { locked_until, .. } => locked_until,
+        };
+
+        match self.pool.users().verify_password(&user.username, &change.current_password).await {
+            Ok(_) => {}
+            Err(sqlx::Error::RowNotFound) => {
+                self.record_audit(user_id, "password_change_failed", ip, user_agent, None).await;
+                return Err(match locked_until {
+                    Some(locked_until) => Self::login_locked(locked_until, now),
+                    None => ServiceError::bad_request("当前密码错误"),
+                });
+            }
+            Err(e) => return Err(ServiceError::internal("修改密码失败", format!("验证密码失败: {}", e))),
+        }
+
+        if let Err(e) = self.pool.login_attempts().clear(&attempt_key).await {
+            tracing::error!("清除密码验证失败计数失败: {}", e);
+        }
+
+        password::check(&change.new_password)?;
+        if change.new_password == change.current_password {
+            return Err(ServiceError::bad_request("新密码不能与当前密码相同"));
+        }
+
+        self.pool.users().update_password(user_id, &change.new_password).await
+            .map_err(|e| ServiceError::internal("修改密码失败", e))?;
+
+        let revoked = self.revoke_other_sessions(user_id, current_token).await?;
+
+        let detail = serde_json::json!({ "revoked_sessions": revoked });
+        self.record_audit(user_id, "password_changed", ip, user_agent, Some(detail)).await;
+
+        Ok(revoked)
+    }
+
+    /// 退出登录
+    pub async fn logout(&self, token: &str) -> Result<(), ServiceError> {
+        self.pool.sessions().delete_session(token).await
+            .map_err(|e| ServiceError::internal("退出登录失败", format!("删除会话失败: {}", e)))
+    }
+
+    /// 获取当前用户信息
+    pub async fn current_user(&self, user_id: i64) -> Result<User, ServiceError> {
+        self.pool.users().find_by_id(user_id).await.map_err(|e| {
+            tracing::error!("获取用户信息失败: {}", e);
+            ServiceError::not_found("用户不存在")
+        })
+    }
+
+    /// 检查用户是否为管理员（用户名在 ADMIN_USERS 中）
+    pub async fn ensure_admin(&self, user_id: i64) -> Result<(), ServiceError> {
+        let user = self.current_user(user_id).await?;
+
+        if !self.config.admin_users.contains(&normalize_username(&user.username)) {
+            return Err(ServiceError::forbidden("需要管理员权限"));
+        }
+
+        Ok(())
+    }
+
+    /// 校验 access token，返回用户ID
+    pub async fn authenticate(&self, token: &str) -> Result<i64, ServiceError> {
+        // 验证 token 是否在会话表中
+        let session = self.pool.sessions().find_by_token(token).await
+            .map_err(|_| ServiceError::unauthorized("令牌已过期或无效"))?;
+
+        // 验证 JWT token，refresh token 不能当作 access token 使用
+        let claims = self.decode_token(token, TOKEN_TYPE_ACCESS)
+            .map_err(|_| ServiceError::unauthorized("无效的令牌"))?;
+
+        // 距上次记录超过间隔才更新活跃时间，且不阻塞当前请求
+        let now = Utc::now();
+        if session.last_seen_at.is_none_or(|seen_at| now - seen_at >= Duration::seconds(LAST_SEEN_INTERVAL_SECONDS)) {
+            let pool = self.pool.clone();
+            tokio::spawn(async move {
+                if let Err(e) = pool.sessions().touch_session(session.id, now).await {
+                    tracing::warn!("更新会话活跃时间失败: {}", e);
+                }
+            });
+        }
+
+        Ok(claims.sub)
+    }
+
+    /// 当前用户的登录会话列表，current_token 所在会话标记为当前会话
+    pub async fn sessions(&self, user_id: i64, current_token: &str) -> Result<Vec<SessionInfo>, ServiceError> {
+        let sessions = self.pool.sessions().find_by_user_id(user_id).await
+            .map_err(|e| ServiceError::internal("获取会话列表失败", e))?;
+
+        Ok(sessions.into_iter()
+            .map(|session| SessionInfo {
+                id: session.id,
+                device: device::describe(session.device_info.as_deref()),
+                current: session.token == current_token,
+                last_seen_at: session.last_seen_at.unwrap_or(session.created_at),
+                device_info: session.device_info,
+                ip_address: session.ip_address,
+                created_at: session.created_at,
+            })
+            .collect())
+    }
+
+    /// 注销指定会话（该设备需重新登录）
+    pub async fn revoke_session(&self, user_id: i64, id: i64) -> Result<(), ServiceError> {
+        match self.pool.sessions().delete_user_session(user_id, id).await {
+            Ok(()) => Ok(()),
+            Err(sqlx::Error::RowNotFound) => Err(ServiceError::not_found("会话不存在")),
+            Err(e) => Err(ServiceError::internal("注销会话失败", e)),
+        }
+    }
+
+    /// 注销除当前会话以外的所有会话，返回注销数量
+    pub async fn revoke_other_sessions(&self, user_id: i64, current_token: &str) -> Result<u64, ServiceError> {
+        let current = self.pool.sessions().find_by_token(current_token).await
+            .map_err(|_| ServiceError::unauthorized("令牌已过期或无效"))?;
+
+        self.pool.sessions().delete_other_sessions(user_id, current.id).await
+            .map_err(|e| ServiceError::internal("注销其他设备失败", e))
+    }
+
+    /// 记录审计事件，写入失败只记录日志，不影响业务结果
+    async fn record_audit(
+        &self,
+        user_id: i64,
+        event: &str,
+        ip: &str,
+        user_agent: Option<String>,
+        detail: Option<serde_json::Value>,
+    ) {
+        let event = NewAuditEvent {
+            user_id: Some(user_id),
+            event: event.to_string(),
+            ip_address: Some(ip.to_string()),
+            user_agent,
+            detail,
+        };
+
+        if let Err(e) = self.pool.audit().record_event(&event).await {
+            tracing::error!("记录审计事件 {} 失败: {}", event.event, e);
+        }
+    }
+
+    /// 吊销令牌族，用于 refresh token 被重复使用时
+    async fn revoke_family(&self, family_id: Option<&str>) {
+        let Some(family_id) = family_id.filter(|family_id| !family_id.is_empty()) else {
+            return;
+        };
+
+        match self.pool.sessions().delete_family(family_id).await {
+            Ok(revoked) => tracing::warn!("refresh token 被重复使用，已吊销令牌族 {}（{} 个会话）", family_id, revoked),
+            Err(e) => tracing::error!("吊销令牌族 {} 失败: {}", family_id, e),
+        }
+    }
+
+    /// 为会话签发一对新的 access token 与 refresh token
+    fn issue_tokens(&self, user_id: i64, family_id: &str) -> Result<SessionTokens, jsonwebtoken::errors::Error> {
+        let now = Utc::now();
+        let expires_at = now + Duration::seconds(self.config.jwt_expires_in);
+        let refresh_expires_at = now + Duration::seconds(self.config.jwt_refresh_expires_in);
+
+        Ok(SessionTokens {
+            token: self.encode_token(user_id, TOKEN_TYPE_ACCESS, None, now, expires_at)?,
+            refresh_token: self.encode_token(user_id, TOKEN_TYPE_REFRESH, Some(family_id), now, refresh_expires_at)?,
+            expires_at,
+            refresh_expires_at,
+        })
+    }
+
+    /// 校验 JWT 签名、有效期与令牌类型
+    fn decode_token(&self, token: &str, typ: &str) -> Result<TokenClaims, jsonwebtoken::errors::Error> {
+        let claims = decode::<TokenClaims>(
+            token,
+            &DecodingKey::from_secret(self.config.jwt_secret.as_bytes()),
+            &Validation::default(),
+        )?
+            .claims;
+
+        if claims.typ != typ {
+            return Err(jsonwebtoken::errors::ErrorKind::InvalidToken.into());
+        }
+        Ok(claims)
+    }
+
+    /// 签发 JWT
+    fn encode_token(
+        &self,
+        user_id: i64,
+        typ: &str,
+        family_id: Option<&str>,
+        issued_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        let claims = TokenClaims {
+            sub: user_id,
+            exp: expires_at.timestamp() as usize,
+            iat: issued_at.timestamp() as usize,
+            typ: typ.to_string(),
+            jti: Uuid::new_v4().to_string(),
+            fam: family_id.map(|family_id| family_id.to_string()),
+        };
+
+        encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(self.config.jwt_secret.as_bytes()),
+        )
+    }
+}
+
+/// 账号的失败计数键：用户名去掉首尾空白并转为小写，最多取 64 个字符
+fn user_attempt_key(username: &str) -> String {
+    let username: String = normalize_username(username).chars().take(64).collect();
+    format!("user:{}", username)
+}
+
+/// 用户名不区分大小写：注册时按此保存，登录时按此查找
+///
+/// MySQL 的默认排序规则本就不区分大小写；其余后端按原样比较，统一规范化后各后端行为一致，
+/// 账号的失败计数也不会因大小写不同而被绕过或被另一个账号共用。
+fn normalize_username(username: &str) -> String {
+    username.trim().to_lowercase()
+}
@@ -0,0 +1,107 @@
+use axum::http::HeaderMap;
+use std::net::IpAddr;
+
+use crate::config::IpNetwork;
+
+/// 客户端 IP
+///
+/// 直连地址不是受信任的代理时直接使用直连地址，转发头可以被客户端伪造；
+/// 否则从右向左查看 X-Forwarded-For，跳过受信任的代理，取第一个不受信任的地址，
+/// 没有 X-Forwarded-For 时使用 X-Real-IP。
+pub fn resolve(peer: Option<IpAddr>, headers: &HeaderMap, trusted_proxies: &[IpNetwork]) -> Option<IpAddr> {
+    let peer = peer?;
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|network| network.contains(ip));
+    if !is_trusted(peer) {
+        return Some(peer);
+    }
+
+    let forwarded: Vec<&str> = headers.get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+
+    if forwarded.is_empty() {
+        return headers.get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .or(Some(peer));
+    }
+
+    let mut client = peer;
+    for hop in forwarded.iter().rev() {
+        // 无法解析的地址之后的内容都不可信，停在最后一个受信任的代理
+        let Ok(ip) = hop.parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(ip) {
+            break;
+        }
+    }
+
+    Some(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    fn proxies() -> Vec<IpNetwork> {
+        vec![IpNetwork::parse("10.0.0.0/8").unwrap(), IpNetwork::parse("::1").unwrap()]
+    }
+
+    fn ip(value: &str) -> Option<IpAddr> {
+        Some(value.parse().unwrap())
+    }
+
+    #[test]
+    fn untrusted_peer_ignores_forwarded_headers() {
+        let headers = headers(&[("x-forwarded-for", "1.2.3.4"), ("x-real-ip", "1.2.3.4")]);
+        assert_eq!(resolve(ip("203.0.113.7"), &headers, &proxies()), ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn trusted_peer_uses_first_untrusted_hop_from_the_right() {
+        let headers = headers(&[("x-forwarded-for", "6.6.6.6, 1.2.3.4"), ("x-forwarded-for", "10.1.1.1")]);
+        assert_eq!(resolve(ip("10.0.0.2"), &headers, &proxies()), ip("1.2.3.4"));
+    }
+
+    #[test]
+    fn trusted_peer_stops_at_invalid_hop() {
+        let headers = headers(&[("x-forwarded-for", "1.2.3.4, bogus, 10.1.1.1")]);
+        assert_eq!(resolve(ip("::1"), &headers, &proxies()), ip("10.1.1.1"));
+    }
+
+    #[test]
+    fn trusted_peer_falls_back_to_real_ip() {
+        assert_eq!(resolve(ip("10.0.0.2"), &headers(&[("x-real-ip", "1.2.3.4")]), &proxies()), ip("1.2.3.4"));
+        assert_eq!(resolve(ip("10.0.0.2"), &headers(&[("x-real-ip", "bogus")]), &proxies()), ip("10.0.0.2"));
+        assert_eq!(resolve(ip("10.0.0.2"), &HeaderMap::new(), &proxies()), ip("10.0.0.2"));
+    }
+
+    #[test]
+    fn ipv4_mapped_peer_matches_ipv4_network() {
+        let headers = headers(&[("x-forwarded-for", "1.2.3.4")]);
+        assert_eq!(resolve(ip("::ffff:10.0.0.2"), &headers, &proxies()), ip("1.2.3.4"));
+    }
+
+    #[test]
+    fn parse_networks() {
+        assert!(IpNetwork::parse("0.0.0.0/0").unwrap().contains("8.8.8.8".parse().unwrap()));
+        assert!(IpNetwork::parse("fd00::/8").unwrap().contains("fd12::1".parse().unwrap()));
+        assert!(!IpNetwork::parse("192.168.1.0/24").unwrap().contains("192.168.2.1".parse().unwrap()));
+        assert_eq!(IpNetwork::parse("10.0.0.0/33"), None);
+        assert_eq!(IpNetwork::parse("localhost"), None);
+    }
+}
@@ -0,0 +1,534 @@
+use chrono::{DateTime, Duration, Utc};
+
+use super::{diff, policy::{self, ReadVia}, qr, search, ttl, ServiceError};
+use crate::{
+    config::Config,
+    database::{
+        models::{
+            ClipContent, ClipFilter, ClipPage, ClipRevision, ClipRevisionSummary, ClipTombstone, CreateClip,
+            RevisionDiff, SearchResponse, SearchResult, SyncResponse, TrashedClip, UpdateClip,
+        },
+        DbPool,
+    },
+};
+
+/// 参与排序的候选结果上限，超过时只取最近更新的这些条，并在响应中标记 truncated
+const SEARCH_CANDIDATES: i64 = 200;
+
+/// 增量同步只下发 updated_at 早于「当前时间 - 该值」的变更。
+/// MySQL 的 DATETIME 精确到秒，同一秒内稍后写入的记录可能排在游标之前，等这一秒过去再下发才不会漏掉
+const SYNC_SETTLE_SECONDS: i64 = 1;
+
+/// 增量同步中的一条变更
+enum SyncEntry {
+    Changed(Box<ClipContent>),
+    Deleted(ClipTombstone),
+}
+
+/// Clip 业务：增删改查与查看计数
+#[derive(Clone)]
+pub struct ClipService {
+    pool: DbPool,
+    config: Config,
+}
+
+impl ClipService {
+    pub fn new(pool: DbPool, config: Config) -> Self {
+        Self { pool, config }
+    }
+
+    /// 创建 Clip
+    ///
+    /// 有效期取 expires_at 或 expires_in，都未指定时使用用户设置的默认有效期；
+    /// 公开的 Clip 受服务端最长有效期限制。
+    pub async fn create(&self, user_id: i64, mut clip_data: CreateClip) -> Result<ClipContent, ServiceError> {
+        if let Some(access_type) = &clip_data.access_type {
+            check_access_type(access_type)?;
+        }
+
+        let expires_at = match (clip_data.expires_at, clip_data.expires_in.take()) {
+            (Some(_), Some(_)) => return Err(ServiceError::bad_request("expires_at 与 expires_in 不能同时指定")),
+            (None, Some(expires_in)) => Some(expires_from_now(&expires_in)?),
+            (Some(expires_at), None) => Some(expires_at),
+            (None, None) => self.default_expires_at(user_id).await?,
+        };
+
+        if expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+            return Err(ServiceError::bad_request("过期时间必须晚于当前时间"));
+        }
+
+        let access_type = clip_data.access_type.as_deref().unwrap_or("private");
+        clip_data.expires_at = self.limit_public_expiry(access_type, expires_at)?;
+        clip_data.max_views = view_limit(clip_data.max_views, clip_data.burn_after_read.take())?;
+
+        let mut clip = self.pool.clips().create_clip(user_id, &clip_data).await
+            .map(|clip| self.with_share_url(clip))
+            .map_err(|e| ServiceError::internal("创建 Clip 失败", e))?;
+
+        // 附带分享链接的二维码，客户端可直接展示；生成失败不影响创建
+        if let Some(share_url) = &clip.share_url {
+            match qr::svg_data_uri(share_url) {
+                Ok(qr_code) => clip.qr_code = Some(qr_code),
+                Err(e) => tracing::warn!("生成二维码失败: {}", e),
+            }
+        }
+        Ok(clip)
+    }
+
+    /// 按筛选条件分页获取用户的 Clips，并返回总数
+    pub async fn list(&self, user_id: i64, filter: &ClipFilter, page: i64, page_size: i64) -> Result<ClipPage, ServiceError> {
+        if page < 1 || !(1..=100).contains(&page_size) {
+            return Err(ServiceError::bad_request("分页参数无效"));
+        }
+
+        let clips = self.pool.clips();
+        let total = clips.count_by_user_id(user_id, filter).await
+            .map_err(|e| ServiceError::internal("获取 Clips 失败", e))?;
+        let items = clips.find_by_user_id(user_id, filter, page, page_size).await
+            .map_err(|e| ServiceError::internal("获取 Clips 失败", e))?
+            .into_iter()
+            .map(|clip| self.with_share_url(clip))
+            .collect();
+
+        Ok(ClipPage {
+            items,
+            total,
+            page,
+            page_size,
+            has_more: page * page_size < total,
+        })
+    }
+
+    /// 在用户自己的 Clips 中全文搜索，按相关度排序并返回高亮片段
+    pub async fn search(&self, user_id: i64, query: &str, limit: i64) -> Result<SearchResponse, ServiceError> {
+        if !(1..=50).contains(&limit) {
+            return Err(ServiceError::bad_request("limit 参数无效"));
+        }
+
+        let terms = search::tokenize(query);
+        if terms.is_empty() {
+            return Err(ServiceError::bad_request("搜索关键词不能为空"));
+        }
+
+        // 多取一条，判断候选集是否被截断
+        let mut candidates = self.pool.clips().search(user_id, &terms, Utc::now(), SEARCH_CANDIDATES + 1).await
+            .map_err(|e| ServiceError::internal("搜索 Clips 失败", e))?;
+        let truncated = candidates.len() as i64 > SEARCH_CANDIDATES;
+        candidates.truncate(SEARCH_CANDIDATES as usize);
+
+        let items = search::rank(candidates, &terms)
+            .into_iter()
+            .take(limit as usize)
+            .map(|result| SearchResult { clip: self.with_share_url(result.clip), ..result })
+            .collect();
+
+        Ok(SearchResponse { items, truncated })
+    }
+
+    /// 增量同步：返回游标之后新建、修改和删除的 Clips
+    pub async fn sync(&self, user_id: i64, since: Option<&str>, limit: i64) -> Result<SyncResponse, ServiceError> {
+        if !(1..=500).contains(&limit) {
+            return Err(ServiceError::bad_request("limit 参数无效"));
+        }
+
+        let since = match since {
+            Some(cursor) => Some(decode_cursor(cursor).ok_or_else(|| ServiceError::bad_request("无效的同步游标"))?),
+            None => None,
+        };
+        let now = Utc::now();
+        let until = now - Duration::seconds(SYNC_SETTLE_SECONDS);
+
+        // 早于墓碑保留期限的游标可能漏掉已被清理的删除记录
+        let retention = Duration::days(self.config.sync_tombstone_retention_days);
+        if since.is_some_and(|(updated_at, _)| updated_at < now - retention) {
+            return Err(ServiceError::gone("同步游标已过期，请不带 since 参数重新全量同步"));
+        }
+
+        // 多取一条判断是否还有下一批
+        let changes = self.pool.clips().find_changes(user_id, since, until, limit + 1).await
+            .map_err(|e| ServiceError::internal("同步 Clips 失败", e))?;
+
+        // 首次同步不需要墓碑；之后把软删除的记录与彻底删除的墓碑按 (时间, id) 合并
+        let tombstones = match since {
+            Some(since) => self.pool.clips().find_tombstones(user_id, since, until, limit + 1).await
+                .map_err(|e| ServiceError::internal("同步 Clips 失败", e))?,
+            None => Vec::new(),
+        };
+
+        let mut entries: Vec<((DateTime<Utc>, i64), SyncEntry)> = changes.into_iter()
+            .map(|change| {
+                let position = (change.clip.updated_at, change.clip.id);
+                match change.deleted_at {
+                    Some(deleted_at) => (position, SyncEntry::Deleted(ClipTombstone { id: change.clip.id, deleted_at })),
+                    None => (position, SyncEntry::Changed(Box::new(change.clip))),
+                }
+            })
+            .chain(tombstones.into_iter().map(|tombstone| ((tombstone.deleted_at, tombstone.id), SyncEntry::Deleted(tombstone))))
+            .collect();
+        entries.sort_by_key(|(position, _)| *position);
+
+        let has_more = entries.len() as i64 > limit;
+        entries.truncate(limit as usize);
+
+        // 没有更多时 until 之前的变更都已下发，游标推进到 until，空闲但持续轮询的客户端游标也不会过期
+        let position = if has_more {
+            entries.last().map(|(position, _)| *position)
+        } else {
+            Some((until, 0))
+        };
+        let cursor = position
+            .map(|(updated_at, id)| encode_cursor(updated_at, id))
+            .unwrap_or_default();
+
+        let mut clips = Vec::new();
+        let mut deleted = Vec::new();
+        for (_, entry) in entries {
+            match entry {
+                SyncEntry::Changed(clip) => clips.push(self.with_share_url(*clip)),
+                SyncEntry::Deleted(tombstone) => deleted.push(tombstone),
+            }
+        }
+
+        Ok(SyncResponse { clips, deleted, cursor, has_more })
+    }
+
+    /// 根据 ID 获取 Clip，并记录查看
+    ///
+    /// 无权读取时与不存在一样返回 404，避免通过 ID 探测他人的 Clip。
+    pub async fn get(&self, id: i64, user_id: i64) -> Result<ClipContent, ServiceError> {
+        let clip = match self.pool.clips().find_by_id(id).await {
+            Ok(clip) if policy::can_read(&clip, Some(user_id), ReadVia::Id) => clip,
+            Ok(_) | Err(sqlx::Error::RowNotFound) => return Err(ServiceError::not_found("Clip 不存在")),
+            Err(e) => return Err(ServiceError::internal("获取 Clip 失败", e)),
+        };
+        ensure_not_expired(&clip)?;
+
+        self.record_view(clip, Some(user_id)).await
+            .map(|clip| self.with_share_url(clip))
+    }
+
+    /// 根据短链接获取 Clip，并记录查看；viewer 为已登录的访问者
+    pub async fn get_by_short_url(&self, short_url: &str, viewer: Option<i64>) -> Result<ClipContent, ServiceError> {
+        let clip = self.find_shared(short_url, viewer).await?;
+        self.view_shared(clip, viewer).await
+    }
+
+    /// 根据短链接查找 Clip，只检查访问权限与有效期，不计入查看次数
+    pub async fn find_shared(&self, short_url: &str, viewer: Option<i64>) -> Result<ClipContent, ServiceError> {
+        let clip = match self.pool.clips().find_by_short_url(short_url).await {
+            Ok(clip) if policy::can_read(&clip, viewer, ReadVia::ShortUrl) => clip,
+            Ok(_) | Err(sqlx::Error::RowNotFound) => return Err(ServiceError::not_found("Clip 不存在")),
+            Err(e) => return Err(ServiceError::internal("获取 Clip 失败", e)),
+        };
+        ensure_not_expired(&clip)?;
+
+        Ok(self.with_share_url(clip))
+    }
+
+    /// 对 find_shared 查到的 Clip 记录一次查看
+    pub async fn view_shared(&self, clip: ClipContent, viewer: Option<i64>) -> Result<ClipContent, ServiceError> {
+        self.record_view(clip, viewer).await
+            .map(|clip| self.with_share_url(clip))
+    }
+
+    /// 更新 Clip
+    ///
+    /// expected_version 为客户端持有的版本号（来自 If-Match），与服务端不一致时返回冲突及当前数据；
+    /// 为空时不做检查，以最后一次写入为准。
+    pub async fn update(
+        &self,
+        id: i64,
+        user_id: i64,
+        mut clip_data: UpdateClip,
+        expected_version: Option<i32>,
+    ) -> Result<ClipContent, ServiceError> {
+        if let Some(access_type) = &clip_data.access_type {
+            check_access_type(access_type)?;
+        }
+
+        if let Some(expires_in) = clip_data.expires_in.take() {
+            if clip_data.expires_at.is_some() {
+                return Err(ServiceError::bad_request("expires_at 与 expires_in 不能同时指定"));
+            }
+            clip_data.expires_at = Some(Some(expires_from_now(&expires_in)?));
+        }
+
+        if clip_data.max_views.is_some() || clip_data.burn_after_read.is_some() {
+            let burn_after_read = clip_data.burn_after_read.take();
+            clip_data.max_views = Some(view_limit(clip_data.max_views.flatten(), burn_after_read)?);
+        }
+
+        // 访问权限或有效期变化时，结合当前数据检查公开 Clip 的最长有效期
+        if clip_data.access_type.is_some() || clip_data.expires_at.is_some() {
+            let current = self.owned_clip(id, user_id).await?;
+            let access_type = clip_data.access_type.as_deref().unwrap_or(&current.access_type);
+            let expires_at = clip_data.expires_at.unwrap_or(current.expires_at);
+
+            let limited = self.limit_public_expiry(access_type, expires_at)?;
+            if limited != expires_at {
+                clip_data.expires_at = Some(limited);
+            }
+        }
+
+        match self.pool.clips().update_clip(id, user_id, &clip_data, expected_version).await {
+            Ok(clip) => Ok(self.with_share_url(clip)),
+            Err(sqlx::Error::RowNotFound) => {
+                // 区分版本冲突与记录不存在
+                match self.pool.clips().find_by_id(id).await {
+                    Ok(current) if current.user_id == user_id && expected_version.is_some() => {
+                        Err(ServiceError::conflict("Clip 已被其他设备修改，请基于最新版本重试", self.with_share_url(current)))
+                    }
+                    _ => Err(ServiceError::not_found("Clip 不存在或无权访问")),
+                }
+            }
+            Err(e) => Err(ServiceError::internal("更新 Clip 失败", e)),
+        }
+    }
+
+    /// 删除 Clip（软删除）
+    pub async fn delete(&self, id: i64, user_id: i64) -> Result<(), ServiceError> {
+        match self.pool.clips().delete_clip(id, user_id).await {
+            Ok(()) => Ok(()),
+            Err(sqlx::Error::RowNotFound) => Err(ServiceError::not_found("Clip 不存在或无权访问")),
+            Err(e) => Err(ServiceError::internal("删除 Clip 失败", e)),
+        }
+    }
+
+    /// 分页获取回收站中的 Clips
+    pub async fn trash(&self, user_id: i64, page: i64, page_size: i64) -> Result<Vec<TrashedClip>, ServiceError> {
+        if page < 1 || !(1..=100).contains(&page_size) {
+            return Err(ServiceError::bad_request("分页参数无效"));
+        }
+
+        let trashed = self.pool.clips().find_trashed(user_id, page, page_size).await
+            .map_err(|e| ServiceError::internal("获取回收站失败", e))?;
+
+        Ok(trashed.into_iter()
+            .map(|trashed| TrashedClip { clip: self.with_share_url(trashed.clip), ..trashed })
+            .collect())
+    }
+
+    /// 从回收站恢复 Clip
+    pub async fn restore(&self, id: i64, user_id: i64) -> Result<ClipContent, ServiceError> {
+        match self.pool.clips().restore_clip(id, user_id).await {
+            Ok(clip) => Ok(self.with_share_url(clip)),
+            Err(sqlx::Error::RowNotFound) => Err(ServiceError::not_found("回收站中没有该 Clip")),
+            Err(e) => Err(ServiceError::internal("恢复 Clip 失败", e)),
+        }
+    }
+
+    /// 彻底删除回收站中的 Clip
+    pub async fn purge(&self, id: i64, user_id: i64) -> Result<(), ServiceError> {
+        match self.pool.clips().purge_clip(id, user_id).await {
+            Ok(()) => Ok(()),
+            Err(sqlx::Error::RowNotFound) => Err(ServiceError::not_found("回收站中没有该 Clip")),
+            Err(e) => Err(ServiceError::internal("彻底删除 Clip 失败", e)),
+        }
+    }
+
+    /// 清空回收站，返回删除数量
+    pub async fn empty_trash(&self, user_id: i64) -> Result<u64, ServiceError> {
+        self.pool.clips().empty_trash(user_id).await
+            .map_err(|e| ServiceError::internal("清空回收站失败", e))
+    }
+
+    /// 获取 Clip 的历史版本列表
+    pub async fn revisions(&self, id: i64, user_id: i64) -> Result<Vec<ClipRevisionSummary>, ServiceError> {
+        self.owned_clip(id, user_id).await?;
+
+        self.pool.clips().find_revisions(id).await
+            .map_err(|e| ServiceError::internal("获取历史版本失败", e))
+    }
+
+    /// 获取 Clip 的指定历史版本
+    pub async fn revision(&self, id: i64, user_id: i64, version: i32) -> Result<ClipRevision, ServiceError> {
+        self.owned_clip(id, user_id).await?;
+
+        match self.pool.clips().find_revision(id, version).await {
+            Ok(revision) => Ok(revision),
+            Err(sqlx::Error::RowNotFound) => Err(ServiceError::not_found("历史版本不存在或已被清理")),
+            Err(e) => Err(ServiceError::internal("获取历史版本失败", e)),
+        }
+    }
+
+    /// 比较两个历史版本的内容，to 缺省时与当前版本比较
+    pub async fn diff_revisions(&self, id: i64, user_id: i64, from: i32, to: Option<i32>) -> Result<RevisionDiff, ServiceError> {
+        let to = match to {
+            Some(to) => to,
+            None => self.owned_clip(id, user_id).await?.version,
+        };
+
+        let old = self.revision(id, user_id, from).await?;
+        let new = self.revision(id, user_id, to).await?;
+
+        Ok(RevisionDiff {
+            from,
+            to,
+            diff: diff::unified_diff(&old.content, &new.content, &format!("v{}", from), &format!("v{}", to)),
+        })
+    }
+
+    /// 以历史版本的标题、内容、标签作为新版本，其余字段保持不变
+    pub async fn restore_revision(
+        &self,
+        id: i64,
+        user_id: i64,
+        version: i32,
+        expected_version: Option<i32>,
+    ) -> Result<ClipContent, ServiceError> {
+        let revision = self.revision(id, user_id, version).await?;
+
+        let clip_data = UpdateClip {
+            title: Some(revision.title),
+            content: Some(revision.content),
+            tags: Some(revision.tags.and_then(|tags| serde_json::from_value(tags).ok())),
+            ..UpdateClip::default()
+        };
+
+        self.update(id, user_id, clip_data, expected_version).await
+    }
+
+    /// 用户设置的默认有效期换算成的过期时间
+    async fn default_expires_at(&self, user_id: i64) -> Result<Option<DateTime<Utc>>, ServiceError> {
+        let settings = self.pool.users().find_settings(user_id).await
+            .map_err(|e| ServiceError::internal("获取用户设置失败", e))?;
+
+        Ok(settings.default_expires_in
+            .as_deref()
+            .and_then(ttl::parse)
+            .map(|ttl| Utc::now() + ttl))
+    }
+
+    /// 公开（public / unlisted）的 Clip 有效期不能超过 PUBLIC_MAX_TTL_DAYS，未设置有效期时取上限
+    fn limit_public_expiry(
+        &self,
+        access_type: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<DateTime<Utc>>, ServiceError> {
+        let max_days = self.config.public_max_ttl_days;
+        if access_type == "private" || max_days <= 0 {
+            return Ok(expires_at);
+        }
+
+        let max_expires_at = Utc::now() + Duration::days(max_days);
+        match expires_at {
+            None => Ok(Some(max_expires_at)),
+            Some(expires_at) if expires_at > max_expires_at => {
+                Err(ServiceError::bad_request(&format!("公开的 Clip 有效期最长 {} 天", max_days)))
+            }
+            Some(expires_at) => Ok(Some(expires_at)),
+        }
+    }
+
+    /// 获取当前用户自己的 Clip
+    async fn owned_clip(&self, id: i64, user_id: i64) -> Result<ClipContent, ServiceError> {
+        match self.pool.clips().find_by_id(id).await {
+            Ok(clip) if clip.user_id == user_id => Ok(clip),
+            Ok(_) | Err(sqlx::Error::RowNotFound) => Err(ServiceError::not_found("Clip 不存在或无权访问")),
+            Err(e) => Err(ServiceError::internal("获取 Clip 失败", e)),
+        }
+    }
+
+    /// 记录一次查看，返回查看后的 Clip
+    ///
+    /// 所有者查看不计数。有查看上限的 Clip 由数据库原子地检查并计数，已被其他读者用完时返回 404；
+    /// 没有上限的 Clip 计数失败只记录日志。
+    async fn record_view(&self, clip: ClipContent, viewer: Option<i64>) -> Result<ClipContent, ServiceError> {
+        if viewer == Some(clip.user_id) {
+            return Ok(clip);
+        }
+
+        match self.pool.clips().view_clip(clip.id).await {
+            Ok(viewed) => Ok(viewed),
+            Err(sqlx::Error::RowNotFound) => Err(ServiceError::not_found("Clip 不存在")),
+            Err(e) if clip.max_views.is_none() => {
+                tracing::error!("增加查看次数失败: {}", e);
+                Ok(clip)
+            }
+            Err(e) => Err(ServiceError::internal("获取 Clip 失败", e)),
+        }
+    }
+
+    /// 填充完整的分享链接 `{PUBLIC_BASE_URL}/s/{short_url}`
+    fn with_share_url(&self, mut clip: ClipContent) -> ClipContent {
+        clip.share_url = clip.short_url.as_ref()
+            .map(|short_url| format!("{}/s/{}", self.config.public_base_url, short_url));
+        clip
+    }
+}
+
+/// 解析 expires_in，换算为过期时间
+fn expires_from_now(expires_in: &str) -> Result<DateTime<Utc>, ServiceError> {
+    ttl::parse(expires_in)
+        .map(|ttl| Utc::now() + ttl)
+        .ok_or_else(|| ServiceError::bad_request("expires_in 格式无效，应为数字加单位，如 10m、1d、1w"))
+}
+
+/// 检查访问类型是否合法
+fn check_access_type(access_type: &str) -> Result<(), ServiceError> {
+    if !policy::ACCESS_TYPES.contains(&access_type) {
+        return Err(ServiceError::bad_request("访问类型只能为 private、unlisted 或 public"));
+    }
+    Ok(())
+}
+
+/// 合并 max_views 与 burn_after_read，返回查看次数上限
+fn view_limit(max_views: Option<i32>, burn_after_read: Option<bool>) -> Result<Option<i32>, ServiceError> {
+    let max_views = match (burn_after_read, max_views) {
+        (Some(true), None | Some(1)) => Some(1),
+        (Some(true), Some(_)) => return Err(ServiceError::bad_request("阅后即焚的 Clip 查看次数上限只能为 1")),
+        (_, max_views) => max_views,
+    };
+
+    if max_views.is_some_and(|max_views| max_views < 1) {
+        return Err(ServiceError::bad_request("查看次数上限必须大于 0"));
+    }
+    Ok(max_views)
+}
+
+/// 已过期的 Clip 返回 410
+fn ensure_not_expired(clip: &ClipContent) -> Result<(), ServiceError> {
+    if clip.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(ServiceError::gone("Clip 已过期"));
+    }
+    Ok(())
+}
+
+/// 同步游标：最后一条变更的 updated_at（纳秒时间戳）与 id
+fn encode_cursor(updated_at: DateTime<Utc>, id: i64) -> String {
+    format!("{}-{}", updated_at.timestamp_nanos_opt().unwrap_or(i64::MAX), id)
+}
+
+fn decode_cursor(cursor: &str) -> Option<(DateTime<Utc>, i64)> {
+    let (nanos, id) = cursor.rsplit_once('-')?;
+    let updated_at = DateTime::from_timestamp_nanos(nanos.parse().ok()?);
+
+    Some((updated_at, id.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let updated_at = DateTime::from_timestamp_nanos(1_792_182_428_056_070_123);
+        let cursor = encode_cursor(updated_at, 42);
+
+        assert_eq!(cursor, "1792182428056070123-42");
+        assert_eq!(decode_cursor(&cursor), Some((updated_at, 42)));
+    }
+
+    #[test]
+    fn cursor_before_epoch_round_trips() {
+        let updated_at = DateTime::from_timestamp_nanos(-1_000);
+        assert_eq!(decode_cursor(&encode_cursor(updated_at, 7)), Some((updated_at, 7)));
+    }
+
+    #[test]
+    fn invalid_cursors_are_rejected() {
+        for cursor in ["", "123", "abc-1", "123-", "123-x", "2026-10-16T00:00:00Z"] {
+            assert_eq!(decode_cursor(cursor), None, "{}", cursor);
+        }
+    }
+}
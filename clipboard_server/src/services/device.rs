@@ -0,0 +1,106 @@
+/// 由 User-Agent 解析出简短的设备名，如 "Chrome 120 · Windows"、"Safari · iOS"
+///
+/// 只识别常见的浏览器、系统与命令行工具，无法识别时返回 "未知设备"。
+pub fn describe(user_agent: Option<&str>) -> String {
+    let Some(user_agent) = user_agent.map(str::trim).filter(|ua| !ua.is_empty()) else {
+        return "未知设备".to_string();
+    };
+
+    let client = client(user_agent);
+    match (client, os(user_agent)) {
+        (Some(client), Some(os)) => format!("{} · {}", client, os),
+        (Some(client), None) => client,
+        (None, Some(os)) => os.to_string(),
+        (None, None) => "未知设备".to_string(),
+    }
+}
+
+/// 客户端名称及主版本号；顺序有意义：Edge、Opera 的 UA 中也包含 Chrome，Chrome 的 UA 中也包含 Safari
+fn client(user_agent: &str) -> Option<String> {
+    const CLIENTS: [(&str, &str); 11] = [
+        ("Tauri/", "网络粘贴板"),
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("CriOS/", "Chrome"),
+        ("Version/", "Safari"),
+        ("curl/", "curl"),
+        ("Wget/", "Wget"),
+        ("python-requests/", "Python"),
+        ("okhttp/", "OkHttp"),
+    ];
+
+    CLIENTS.iter()
+        .find_map(|(marker, name)| {
+            let start = user_agent.find(marker)? + marker.len();
+            let major: String = user_agent[start..].chars().take_while(char::is_ascii_digit).collect();
+            Some(if major.is_empty() || *name == "Safari" {
+                name.to_string()
+            } else {
+                format!("{} {}", name, major)
+            })
+        })
+}
+
+fn os(user_agent: &str) -> Option<&'static str> {
+    const SYSTEMS: [(&str, &str); 8] = [
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Android", "Android"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("Macintosh", "macOS"),
+        ("CrOS", "ChromeOS"),
+        ("Linux", "Linux"),
+    ];
+
+    SYSTEMS.iter()
+        .find(|(marker, _)| user_agent.contains(marker))
+        .map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_browsers() {
+        let cases = [
+            (
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+                "Chrome 120 · Windows",
+            ),
+            (
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.2210.91",
+                "Edge 120 · Windows",
+            ),
+            (
+                "Mozilla/5.0 (iPhone; CPU iPhone OS 17_2 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Mobile/15E148 Safari/604.1",
+                "Safari · iOS",
+            ),
+            (
+                "Mozilla/5.0 (Macintosh; Intel Mac OS X 14.2; rv:121.0) Gecko/20100101 Firefox/121.0",
+                "Firefox 121 · macOS",
+            ),
+            (
+                "Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.144 Mobile Safari/537.36",
+                "Chrome 120 · Android",
+            ),
+        ];
+
+        for (user_agent, expected) in cases {
+            assert_eq!(describe(Some(user_agent)), expected);
+        }
+    }
+
+    #[test]
+    fn describe_tools_and_unknown() {
+        assert_eq!(describe(Some("curl/8.4.0")), "curl 8");
+        assert_eq!(describe(Some("python-requests/2.31.0")), "Python 2");
+        assert_eq!(describe(Some("X11; Linux x86_64")), "Linux");
+        assert_eq!(describe(Some("SomeBot")), "未知设备");
+        assert_eq!(describe(Some("   ")), "未知设备");
+        assert_eq!(describe(None), "未知设备");
+    }
+}
@@ -0,0 +1,207 @@
+/// unified diff 中变更前后保留的上下文行数
+const CONTEXT: usize = 3;
+/// 最短编辑距离的计算上限，超过后不再求最优解，按整段替换输出
+const MAX_EDITS: isize = 1000;
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// 按行比较两段文本，生成 unified diff；内容相同时返回空字符串
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&a, &b);
+
+    // 相邻两处变更之间的相同行不超过 2 * CONTEXT 时合并为一个 hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, op) in ops.iter().enumerate() {
+        if matches!(op, Op::Equal(..)) {
+            continue;
+        }
+        match hunks.last_mut() {
+            Some(hunk) if i - hunk.1 <= 2 * CONTEXT + 1 => hunk.1 = i,
+            _ => hunks.push((i, i)),
+        }
+    }
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut diff = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (first, last) in hunks {
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(ops.len());
+        let hunk = &ops[start..end];
+
+        let (old_before, new_before) = ops[..start].iter().fold((0, 0), |(o, n), op| match op {
+            Op::Equal(..) => (o + 1, n + 1),
+            Op::Delete(_) => (o + 1, n),
+            Op::Insert(_) => (o, n + 1),
+        });
+        let old_count = hunk.iter().filter(|op| !matches!(op, Op::Insert(_))).count();
+        let new_count = hunk.iter().filter(|op| !matches!(op, Op::Delete(_))).count();
+
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_before, old_count),
+            hunk_range(new_before, new_count),
+        ));
+        for op in hunk {
+            let (prefix, line) = match *op {
+                Op::Equal(i, _) => (' ', a[i]),
+                Op::Delete(i) => ('-', a[i]),
+                Op::Insert(j) => ('+', b[j]),
+            };
+            diff.push(prefix);
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+/// hunk 头中的行范围：行数为 0 时起始行取前一行，行数为 1 时省略行数（与 GNU diff 一致）
+fn hunk_range(before: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", before),
+        1 => format!("{}", before + 1),
+        _ => format!("{},{}", before + 1, count),
+    }
+}
+
+/// 去掉相同的首尾行后，对中间部分求最短编辑脚本
+fn diff_ops(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+
+    let middle = myers(a_mid, b_mid).unwrap_or_else(|| {
+        (0..a_mid.len()).map(Op::Delete)
+            .chain((0..b_mid.len()).map(Op::Insert))
+            .collect()
+    });
+
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+    ops.extend(middle.into_iter().map(|op| match op {
+        Op::Equal(i, j) => Op::Equal(i + prefix, j + prefix),
+        Op::Delete(i) => Op::Delete(i + prefix),
+        Op::Insert(j) => Op::Insert(j + prefix),
+    }));
+    ops.extend((0..suffix).map(|i| Op::Equal(a.len() - suffix + i, b.len() - suffix + i)));
+    ops
+}
+
+/// Myers 差分算法，编辑距离超过 MAX_EDITS 时返回 None
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Op>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let offset = max + 1;
+    let mut v = vec![0isize; (2 * max + 3) as usize];
+    // trace[d] 为第 d 轮开始前 v 在 k ∈ [-d-1, d+1] 上的取值，回溯时使用
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    let mut distance = None;
+    'search: for d in 0..=max.min(MAX_EDITS) {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+
+        for k in (-d..=d).step_by(2) {
+            let i = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) { v[i + 1] } else { v[i - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+
+            if x >= n && y >= m {
+                distance = Some(d);
+                break 'search;
+            }
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (0..=distance?).rev() {
+        let snapshot = &trace[d as usize];
+        let get = |k: isize| snapshot[(k + d + 1) as usize];
+
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) { k + 1 } else { k - 1 };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push(Op::Equal(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                y -= 1;
+                ops.push(Op::Insert(y as usize));
+            } else {
+                x -= 1;
+                ops.push(Op::Delete(x as usize));
+            }
+        }
+    }
+
+    ops.reverse();
+    Some(ops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_texts_have_no_diff() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "v1", "v2"), "");
+    }
+
+    #[test]
+    fn changed_line_with_context() {
+        let diff = unified_diff("a\nb\nc\n", "a\nB\nc\n", "v1", "v2");
+        assert_eq!(diff, "--- v1\n+++ v2\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n");
+    }
+
+    #[test]
+    fn insert_into_empty_text() {
+        let diff = unified_diff("", "x\ny", "v1", "v2");
+        assert_eq!(diff, "--- v1\n+++ v2\n@@ -0,0 +1,2 @@\n+x\n+y\n");
+    }
+
+    #[test]
+    fn distant_changes_split_into_hunks() {
+        let old: Vec<String> = (1..=20).map(|i| i.to_string()).collect();
+        let mut new = old.clone();
+        new[1] = "two".to_string();
+        new[18] = "nineteen".to_string();
+
+        let diff = unified_diff(&old.join("\n"), &new.join("\n"), "v1", "v2");
+        let headers: Vec<&str> = diff.lines().filter(|line| line.starts_with("@@")).collect();
+        assert_eq!(headers, ["@@ -1,5 +1,5 @@", "@@ -16,5 +16,5 @@"]);
+        assert!(diff.contains("-2\n+two\n") && diff.contains("-19\n+nineteen\n"));
+    }
+
+    #[test]
+    fn large_edit_falls_back_to_replacement() {
+        let old: Vec<String> = (0..600).map(|i| format!("o{}", i)).collect();
+        let new: Vec<String> = (0..600).map(|i| format!("n{}", i)).collect();
+
+        let diff = unified_diff(&old.join("\n"), &new.join("\n"), "v1", "v2");
+        let body: Vec<&str> = diff.lines().skip(3).collect();
+        assert_eq!(diff.lines().nth(2), Some("@@ -1,600 +1,600 @@"));
+        assert_eq!((body[0], body[599], body[600], body[1199]), ("-o0", "-o599", "+n0", "+n599"));
+    }
+}
@@ -0,0 +1,117 @@
+pub mod auth;
+pub mod client_ip;
+pub mod clips;
+pub mod device;
+pub mod diff;
+pub mod page;
+pub mod password;
+pub mod policy;
+pub mod qr;
+pub mod raw;
+pub mod rate_limit;
+pub mod scheduler;
+pub mod search;
+pub mod ttl;
+pub mod users;
+
+pub use auth::AuthService;
+pub use clips::ClipService;
+pub use rate_limit::RateLimiter;
+pub use scheduler::Scheduler;
+pub use users::UserService;
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use std::fmt::Display;
+
+use crate::database::models::ApiResponse;
+
+/// 业务层错误，由处理器直接转换为 HTTP 响应
+#[derive(Debug)]
+pub enum ServiceError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    /// 资源曾经存在但已过期
+    Gone(String),
+    /// 并发修改冲突，附带服务端当前数据
+    Conflict(String, serde_json::Value),
+    /// 条件请求的前提不成立（如无法满足的 If-Match）
+    PreconditionFailed(String),
+    /// 请求过于频繁，附带建议的重试等待秒数
+    TooManyRequests(String, u64),
+    Internal(String),
+}
+
+impl ServiceError {
+    pub fn bad_request(message: &str) -> Self {
+        ServiceError::BadRequest(message.to_string())
+    }
+
+    pub fn unauthorized(message: &str) -> Self {
+        ServiceError::Unauthorized(message.to_string())
+    }
+
+    pub fn forbidden(message: &str) -> Self {
+        ServiceError::Forbidden(message.to_string())
+    }
+
+    pub fn not_found(message: &str) -> Self {
+        ServiceError::NotFound(message.to_string())
+    }
+
+    pub fn gone(message: &str) -> Self {
+        ServiceError::Gone(message.to_string())
+    }
+
+    pub fn conflict(message: &str, current: impl Serialize) -> Self {
+        ServiceError::Conflict(message.to_string(), serde_json::to_value(current).unwrap_or_default())
+    }
+
+    pub fn precondition_failed(message: &str) -> Self {
+        ServiceError::PreconditionFailed(message.to_string())
+    }
+
+    pub fn too_many_requests(message: &str, retry_after: u64) -> Self {
+        ServiceError::TooManyRequests(message.to_string(), retry_after)
+    }
+
+    /// 记录内部错误日志，对外只返回概要信息
+    pub fn internal(message: &str, error: impl Display) -> Self {
+        tracing::error!("{}: {}", message, error);
+        ServiceError::Internal(message.to_string())
+    }
+}
+
+impl IntoResponse for ServiceError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ServiceError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ServiceError::Unauthorized(message) => (StatusCode::UNAUTHORIZED, message),
+            ServiceError::Forbidden(message) => (StatusCode::FORBIDDEN, message),
+            ServiceError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ServiceError::Gone(message) => (StatusCode::GONE, message),
+            ServiceError::Conflict(message, current) => {
+                let response = ApiResponse {
+                    status: "error".to_string(),
+                    data: Some(current),
+                    message,
+                };
+                return (StatusCode::CONFLICT, Json(response)).into_response();
+            }
+            ServiceError::PreconditionFailed(message) => (StatusCode::PRECONDITION_FAILED, message),
+            ServiceError::TooManyRequests(message, retry_after) => {
+                let headers = [(header::RETRY_AFTER, retry_after.to_string())];
+                return (StatusCode::TOO_MANY_REQUESTS, headers, Json(ApiResponse::<()>::error(&message))).into_response();
+            }
+            ServiceError::Internal(message) => (StatusCode::INTERNAL_SERVER_ERROR, message),
+        };
+
+        (status, Json(ApiResponse::<()>::error(&message))).into_response()
+    }
+}
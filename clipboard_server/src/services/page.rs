@@ -0,0 +1,308 @@
+use std::sync::OnceLock;
+
+use pulldown_cmark::{html, Options, Parser};
+use syntect::{highlighting::ThemeSet, html::highlighted_html_for_string, parsing::SyntaxSet};
+
+use crate::database::models::ClipContent;
+
+/// 页面标题与 og:site_name
+const SITE_NAME: &str = "网络粘贴板";
+/// 超过该长度（字节）的代码不做语法高亮，避免单次请求占用过多 CPU
+const MAX_HIGHLIGHT_BYTES: usize = 200 * 1024;
+/// og:description 的最大字符数
+const DESCRIPTION_CHARS: usize = 200;
+/// 语法高亮主题
+const THEME: &str = "InspiredGitHub";
+
+/// 分享页的 Content-Security-Policy：只允许带 nonce 的内联脚本，禁止外部脚本、表单与嵌入
+pub fn content_security_policy(nonce: &str) -> String {
+    format!(
+        "default-src 'none'; script-src 'nonce-{}'; style-src 'unsafe-inline'; img-src https: data:; \
+         base-uri 'none'; form-action 'none'; frame-ancestors 'none'",
+        nonce
+    )
+}
+
+/// 渲染 Clip 分享页
+///
+/// 代码按 language 高亮，Markdown 渲染后经 ammonia 清洗，其余内容原样转义显示。
+/// raw_url 为原始内容按钮的地址，nonce 须与 content_security_policy 一致。
+pub fn share_page(clip: &ClipContent, raw_url: &str, nonce: &str) -> String {
+    let body = if clip.is_encrypted != 0 {
+        plain(&clip.content)
+    } else {
+        match clip.content_type.as_str() {
+            "markdown" => format!("<article class=\"markdown\">{}</article>", markdown(&clip.content)),
+            "code" => code(&clip.content, clip.language.as_deref()),
+            "url" => link(&clip.content),
+            _ => plain(&clip.content),
+        }
+    };
+
+    let mut meta = Vec::new();
+    if let Some(language) = clip.language.as_deref().filter(|language| !language.is_empty()) {
+        meta.push(escape(language));
+    }
+    meta.push(format!("{} 次查看", clip.view_count));
+    meta.push(format!("更新于 {}", clip.updated_at.format("%Y-%m-%d %H:%M UTC")));
+    if let Some(expires_at) = clip.expires_at {
+        meta.push(format!("{} 过期", expires_at.format("%Y-%m-%d %H:%M UTC")));
+    }
+    if let Some(max_views) = clip.max_views {
+        meta.push(format!("最多查看 {} 次", max_views));
+    }
+
+    let actions = format!(
+        "<button type=\"button\" id=\"copy\">复制</button>\
+         <a class=\"button\" href=\"{}\">原始内容</a>\
+         <textarea id=\"content\" hidden>{}</textarea>\
+         <script nonce=\"{}\">{}</script>",
+        escape(raw_url),
+        escape(&clip.content),
+        escape(nonce),
+        COPY_SCRIPT,
+    );
+
+    layout(
+        &title(clip),
+        &og_tags(clip, &description(&clip.content)),
+        &format!(
+            "<header><h1>{}</h1><p class=\"meta\">{}</p><nav>{}</nav></header><main>{}</main>",
+            escape(&title(clip)),
+            meta.join(" · "),
+            actions,
+            body,
+        ),
+    )
+}
+
+/// 有查看次数上限的 Clip 先显示确认页，避免聊天软件抓取链接预览时消耗查看次数
+pub fn confirm_page(clip: &ClipContent) -> String {
+    let remaining = clip.max_views.unwrap_or(0) - clip.view_count;
+    let notice = if remaining <= 1 {
+        "该 Clip 查看后将被销毁，只能查看这一次。".to_string()
+    } else {
+        format!("该 Clip 还可被查看 {} 次，达到上限后将被销毁。", remaining)
+    };
+
+    layout(
+        &title(clip),
+        &og_tags(clip, &notice),
+        &format!(
+            "<header><h1>{}</h1></header><main><p>{}</p>\
+             <nav><a class=\"button\" href=\"?confirm=1\">查看内容</a></nav></main>",
+            escape(&title(clip)),
+            notice,
+        ),
+    )
+}
+
+/// 错误页（Clip 不存在、已过期等）
+pub fn error_page(message: &str) -> String {
+    layout(
+        message,
+        "",
+        &format!("<header><h1>{}</h1></header><main><p>链接可能已失效、被删除或无权访问。</p></main>", escape(message)),
+    )
+}
+
+fn layout(title: &str, head: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html lang=\"zh-CN\"><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <meta name=\"robots\" content=\"noindex, nofollow\">\
+         <title>{} - {}</title>{}<style>{}</style></head><body>{}</body></html>",
+        escape(title),
+        SITE_NAME,
+        head,
+        STYLE,
+        body,
+    )
+}
+
+/// OpenGraph 与 Twitter Card 标签，用于聊天软件中的链接预览
+fn og_tags(clip: &ClipContent, description: &str) -> String {
+    let mut tags = vec![
+        ("og:type", "article".to_string()),
+        ("og:site_name", SITE_NAME.to_string()),
+        ("og:title", title(clip)),
+        ("og:description", description.to_string()),
+    ];
+    if let Some(share_url) = &clip.share_url {
+        tags.push(("og:url", share_url.clone()));
+    }
+
+    let mut html = format!(
+        "<meta name=\"description\" content=\"{}\"><meta name=\"twitter:card\" content=\"summary\">",
+        escape(description)
+    );
+    for (property, content) in tags {
+        html.push_str(&format!("<meta property=\"{}\" content=\"{}\">", property, escape(&content)));
+    }
+    html
+}
+
+fn title(clip: &ClipContent) -> String {
+    clip.title.clone()
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or_else(|| format!("Clip {}", clip.short_url.as_deref().unwrap_or("")))
+}
+
+/// 正文开头的一段，空白折叠为单个空格
+fn description(content: &str) -> String {
+    let collapsed = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut description: String = collapsed.chars().take(DESCRIPTION_CHARS).collect();
+    if collapsed.chars().count() > DESCRIPTION_CHARS {
+        description.push('…');
+    }
+    description
+}
+
+/// Markdown 渲染为 HTML，再用 ammonia 去除脚本、事件属性、javascript: 链接等危险内容
+fn markdown(content: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, Parser::new_ext(content, options));
+    ammonia::clean(&rendered)
+}
+
+/// 按 language 做语法高亮，语言未知或内容过长时按纯文本显示
+fn code(content: &str, language: Option<&str>) -> String {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+
+    let Some(language) = language.map(str::trim).filter(|language| !language.is_empty()) else {
+        return plain(content);
+    };
+    if content.len() > MAX_HIGHLIGHT_BYTES {
+        return plain(content);
+    }
+
+    let syntaxes = SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines);
+    let themes = THEMES.get_or_init(ThemeSet::load_defaults);
+    let (Some(syntax), Some(theme)) = (syntaxes.find_syntax_by_token(language), themes.themes.get(THEME)) else {
+        return plain(content);
+    };
+
+    highlighted_html_for_string(content, syntaxes, syntax, theme).unwrap_or_else(|e| {
+        tracing::warn!("语法高亮失败: {}", e);
+        plain(content)
+    })
+}
+
+/// url 类型：http(s) 链接可点击，其余按纯文本显示
+fn link(content: &str) -> String {
+    let url = content.trim();
+    let is_web_url = (url.starts_with("https://") || url.starts_with("http://"))
+        && !url.chars().any(char::is_whitespace);
+    if !is_web_url {
+        return plain(content);
+    }
+    format!(
+        "<p class=\"url\"><a href=\"{0}\" rel=\"nofollow noopener noreferrer\">{0}</a></p>",
+        escape(url)
+    )
+}
+
+fn plain(content: &str) -> String {
+    format!("<pre>{}</pre>", escape(content))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const COPY_SCRIPT: &str = "document.getElementById('copy').addEventListener('click', function () {\
+    var button = this;\
+    navigator.clipboard.writeText(document.getElementById('content').value).then(function () {\
+        button.textContent = '已复制';\
+        setTimeout(function () { button.textContent = '复制'; }, 1500);\
+    }, function () { button.textContent = '复制失败'; });\
+});";
+
+const STYLE: &str = "body{margin:0 auto;max-width:960px;padding:16px;font:15px/1.6 -apple-system,BlinkMacSystemFont,'Segoe UI',\
+'PingFang SC','Microsoft YaHei',sans-serif;color:#24292f}\
+h1{font-size:22px;margin:0 0 4px;word-break:break-all}.meta{color:#57606a;font-size:13px;margin:0 0 12px}\
+nav{display:flex;gap:8px;margin-bottom:16px}\
+button,.button{padding:4px 14px;border:1px solid #d0d7de;border-radius:6px;background:#f6f8fa;color:#24292f;\
+font-size:14px;text-decoration:none;cursor:pointer}\
+pre{padding:12px;border:1px solid #d0d7de;border-radius:6px;overflow:auto;white-space:pre-wrap;word-break:break-all;\
+font:13px/1.5 ui-monospace,SFMono-Regular,Menlo,Consolas,monospace}\
+.markdown img{max-width:100%}.markdown table{border-collapse:collapse}\
+.markdown td,.markdown th{border:1px solid #d0d7de;padding:4px 8px}.url{word-break:break-all}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(content_type: &str, content: &str) -> ClipContent {
+        ClipContent {
+            title: Some("title".to_string()),
+            content: content.to_string(),
+            content_type: content_type.to_string(),
+            access_type: "unlisted".to_string(),
+            share_url: Some("http://clip.test/s/abcd1234".to_string()),
+            ..ClipContent::fixture()
+        }
+    }
+
+    #[test]
+    fn markdown_removes_scripts_and_javascript_links() {
+        let html = markdown("# hi\n\n<script>alert(1)</script>\n\n[x](javascript:alert(1)) <img src=x onerror=alert(1)>");
+
+        assert!(html.contains("<h1>hi</h1>"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("onerror"));
+    }
+
+    #[test]
+    fn og_tags_escape_quotes() {
+        let mut clip = clip("text", "");
+        clip.title = Some("a\" onmouseover=\"x".to_string());
+
+        let html = og_tags(&clip, "say \"hi\" <b>");
+        assert!(html.contains("<meta property=\"og:title\" content=\"a&quot; onmouseover=&quot;x\">"));
+        assert!(html.contains("<meta name=\"description\" content=\"say &quot;hi&quot; &lt;b&gt;\">"));
+        assert!(!html.contains("\" onmouseover"));
+    }
+
+    #[test]
+    fn link_only_accepts_web_urls() {
+        assert_eq!(
+            link(" https://example.com/?a=1&b=2 "),
+            "<p class=\"url\"><a href=\"https://example.com/?a=1&amp;b=2\" rel=\"nofollow noopener noreferrer\">\
+             https://example.com/?a=1&amp;b=2</a></p>"
+        );
+        for url in ["javascript:alert(1)", "data:text/html,<b>x</b>", "https://a.test/ onclick=x", "ftp://a.test"] {
+            assert!(link(url).starts_with("<pre>"), "{}", url);
+        }
+
+        let page = share_page(&clip("url", "javascript:alert(1)"), "/r/abcd1234", "n");
+        assert!(page.contains("<pre>javascript:alert(1)</pre>"));
+        assert!(!page.contains("href=\"javascript:"));
+    }
+
+    #[test]
+    fn share_page_script_nonce_matches_csp() {
+        let nonce = "dGVzdC1ub25jZQ";
+        let page = share_page(&clip("text", "</textarea><script>alert(1)</script>"), "/r/abcd1234", nonce);
+
+        assert!(content_security_policy(nonce).contains(&format!("script-src 'nonce-{}'", nonce)));
+        assert!(page.contains(&format!("<script nonce=\"{}\">", nonce)));
+        // 只有带 nonce 的复制脚本一个 script 标签，正文中的标签被转义
+        assert_eq!(page.matches("<script").count(), 1);
+        assert!(page.contains("&lt;/textarea&gt;&lt;script&gt;"));
+    }
+}
@@ -0,0 +1,54 @@
+use super::ServiceError;
+
+/// 密码最短字符数（需求 §2.1）
+const MIN_CHARS: usize = 6;
+/// bcrypt 只使用输入的前 72 字节
+const BCRYPT_MAX_BYTES: usize = 72;
+/// 盐值（UUID 字符串）的字节数，哈希输入为密码在前、盐值在后
+const SALT_BYTES: usize = 36;
+/// 密码最长字节数：保证密码与完整的盐值都落在 bcrypt 使用的前 72 字节内
+const MAX_BYTES: usize = BCRYPT_MAX_BYTES - SALT_BYTES;
+
+/// 密码策略：至少 6 位，不超过 36 字节，不能全为空白，不能包含控制字符
+pub fn check(password: &str) -> Result<(), ServiceError> {
+    if password.chars().count() < MIN_CHARS {
+        return Err(ServiceError::BadRequest(format!("密码至少 {} 位", MIN_CHARS)));
+    }
+    if password.len() > MAX_BYTES {
+        return Err(ServiceError::BadRequest(format!("密码不能超过 {} 字节", MAX_BYTES)));
+    }
+    if password.trim().is_empty() {
+        return Err(ServiceError::bad_request("密码不能全为空白字符"));
+    }
+    if password.chars().any(char::is_control) {
+        return Err(ServiceError::bad_request("密码不能包含控制字符"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_accepts_valid_passwords() {
+        assert!(check("secret1").is_ok());
+        assert!(check(&"a".repeat(MAX_BYTES)).is_ok());
+        assert!(check("密码密码密码").is_ok());
+    }
+
+    #[test]
+    fn check_rejects_invalid_passwords() {
+        for password in ["short", "      ", "secret\n1"] {
+            assert!(check(password).is_err(), "{:?}", password);
+        }
+    }
+
+    #[test]
+    fn max_bytes_leaves_room_for_salt() {
+        assert_eq!(uuid::Uuid::new_v4().to_string().len(), SALT_BYTES);
+        assert!(check(&"a".repeat(MAX_BYTES + 1)).is_err());
+        // 13 个三字节汉字共 39 字节
+        assert!(check(&"密".repeat(13)).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use crate::database::models::ClipContent;
+
+/// 合法的访问类型
+pub const ACCESS_TYPES: [&str; 3] = ["private", "unlisted", "public"];
+
+/// 读取 Clip 的途径
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadVia {
+    /// 通过 ID 读取，ID 连续、可被遍历
+    Id,
+    /// 通过短链接读取，持有链接即可访问
+    ShortUrl,
+}
+
+/// Clip 读取权限
+///
+/// - 所有者总是可以读取；
+/// - public：任何人可读；
+/// - unlisted：持有短链接的人可读，不能通过 ID 读取；
+/// - private 及未知类型：仅所有者可读。
+pub fn can_read(clip: &ClipContent, viewer: Option<i64>, via: ReadVia) -> bool {
+    if viewer == Some(clip.user_id) {
+        return true;
+    }
+
+    match clip.access_type.as_str() {
+        "public" => true,
+        "unlisted" => via == ReadVia::ShortUrl,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: i64 = 1;
+
+    fn clip(access_type: &str) -> ClipContent {
+        ClipContent { id: 10, user_id: OWNER, access_type: access_type.to_string(), ..ClipContent::fixture() }
+    }
+
+    #[test]
+    fn can_read_matrix() {
+        let owner = Some(OWNER);
+        let other = Some(2);
+        let anonymous = None;
+
+        // (访问类型, 访问者, 途径, 是否可读)
+        let cases = [
+            ("public", owner, ReadVia::Id, true),
+            ("public", owner, ReadVia::ShortUrl, true),
+            ("public", other, ReadVia::Id, true),
+            ("public", other, ReadVia::ShortUrl, true),
+            ("public", anonymous, ReadVia::Id, true),
+            ("public", anonymous, ReadVia::ShortUrl, true),
+            ("unlisted", owner, ReadVia::Id, true),
+            ("unlisted", owner, ReadVia::ShortUrl, true),
+            ("unlisted", other, ReadVia::Id, false),
+            ("unlisted", other, ReadVia::ShortUrl, true),
+            ("unlisted", anonymous, ReadVia::Id, false),
+            ("unlisted", anonymous, ReadVia::ShortUrl, true),
+            ("private", owner, ReadVia::Id, true),
+            ("private", owner, ReadVia::ShortUrl, true),
+            ("private", other, ReadVia::Id, false),
+            ("private", other, ReadVia::ShortUrl, false),
+            ("private", anonymous, ReadVia::Id, false),
+            ("private", anonymous, ReadVia::ShortUrl, false),
+            ("friends", owner, ReadVia::Id, true),
+            ("friends", owner, ReadVia::ShortUrl, true),
+            ("friends", other, ReadVia::Id, false),
+            ("friends", other, ReadVia::ShortUrl, false),
+            ("friends", anonymous, ReadVia::Id, false),
+            ("friends", anonymous, ReadVia::ShortUrl, false),
+        ];
+
+        for (access_type, viewer, via, expected) in cases {
+            assert_eq!(can_read(&clip(access_type), viewer, via), expected, "{} {:?} {:?}", access_type, viewer, via);
+        }
+    }
+
+    #[test]
+    fn access_types_are_case_sensitive() {
+        assert!(!can_read(&clip("Public"), None, ReadVia::ShortUrl));
+        assert!(!can_read(&clip("UNLISTED"), None, ReadVia::ShortUrl));
+    }
+}
@@ -0,0 +1,140 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use qrcode::{render::svg, Color, EcLevel, QrCode};
+
+use crate::database::models::ClipContent;
+
+/// 默认边长（像素）
+pub const DEFAULT_SIZE: u32 = 256;
+/// 允许的边长范围（像素）
+pub const MIN_SIZE: u32 = 64;
+pub const MAX_SIZE: u32 = 2048;
+/// 二维码四周空白的模块数（规范要求至少 4 个）
+const QUIET_ZONE: usize = 4;
+/// 二维码最长缓存时间（秒）
+const MAX_AGE_SECONDS: i64 = 86400;
+
+/// 解析纠错等级 L / M / Q / H（不区分大小写），缺省为 M
+pub fn parse_ec_level(level: Option<&str>) -> Option<EcLevel> {
+    match level.map(|level| level.trim().to_ascii_uppercase()).as_deref() {
+        None | Some("M") => Some(EcLevel::M),
+        Some("L") => Some(EcLevel::L),
+        Some("Q") => Some(EcLevel::Q),
+        Some("H") => Some(EcLevel::H),
+        Some(_) => None,
+    }
+}
+
+/// 生成 SVG 格式的二维码，size 为最小边长（像素）
+pub fn svg(data: &str, size: u32, ec_level: EcLevel) -> Result<String, String> {
+    let code = QrCode::with_error_correction_level(data, ec_level).map_err(|e| e.to_string())?;
+    Ok(code.render::<svg::Color>()
+        .min_dimensions(size, size)
+        .build())
+}
+
+/// 生成 PNG 格式的二维码（8 位灰度），边长为不小于 size 的模块整数倍
+pub fn png(data: &str, size: u32, ec_level: EcLevel) -> Result<Vec<u8>, String> {
+    let code = QrCode::with_error_correction_level(data, ec_level).map_err(|e| e.to_string())?;
+    let modules = code.width();
+    let colors = code.to_colors();
+
+    let total = modules + 2 * QUIET_ZONE;
+    let scale = (size as usize).div_ceil(total).max(1);
+    let width = total * scale;
+
+    let mut pixels = vec![0xFFu8; width * width];
+    for (i, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let (x, y) = ((i % modules + QUIET_ZONE) * scale, (i / modules + QUIET_ZONE) * scale);
+        for row in y..y + scale {
+            pixels[row * width + x..row * width + x + scale].fill(0);
+        }
+    }
+
+    let mut buffer = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buffer, width as u32, width as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| e.to_string())?;
+    Ok(buffer)
+}
+
+/// 默认参数的 SVG 二维码，编码为 data URI，可直接用作 <img> 的 src
+pub fn svg_data_uri(data: &str) -> Result<String, String> {
+    svg(data, DEFAULT_SIZE, EcLevel::M)
+        .map(|svg| format!("data:image/svg+xml;base64,{}", STANDARD.encode(svg)))
+}
+
+/// 二维码的 Cache-Control 头
+///
+/// 二维码只由分享链接决定，但不能比 Clip 本身活得更久：只有公开且不限查看次数的 Clip
+/// 允许共享缓存，并且最多缓存到过期为止；其余 Clip 的二维码不允许缓存。
+pub fn cache_control(clip: &ClipContent, now: DateTime<Utc>) -> String {
+    if clip.access_type != "public" || clip.max_views.is_some() {
+        return "private, no-store".to_string();
+    }
+
+    let max_age = clip.expires_at
+        .map(|expires_at| (expires_at - now).num_seconds().clamp(0, MAX_AGE_SECONDS))
+        .unwrap_or(MAX_AGE_SECONDS);
+    if max_age == 0 {
+        return "private, no-store".to_string();
+    }
+
+    format!("public, max-age={}", max_age)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn clip(access_type: &str) -> ClipContent {
+        ClipContent { access_type: access_type.to_string(), ..ClipContent::fixture() }
+    }
+
+    #[test]
+    fn parse_ec_levels() {
+        assert_eq!(parse_ec_level(None), Some(EcLevel::M));
+        assert_eq!(parse_ec_level(Some(" h ")), Some(EcLevel::H));
+        assert_eq!(parse_ec_level(Some("L")), Some(EcLevel::L));
+        assert_eq!(parse_ec_level(Some("X")), None);
+    }
+
+    #[test]
+    fn png_is_at_least_size() {
+        let png = png("http://clip.test/s/abcd1234", MIN_SIZE, EcLevel::M).unwrap();
+        let decoder = png::Decoder::new(png.as_slice());
+        let info = decoder.read_info().unwrap().info().clone();
+
+        assert_eq!(info.width, info.height);
+        assert!(info.width >= MIN_SIZE);
+    }
+
+    #[test]
+    fn cache_control_by_visibility_and_expiry() {
+        let now = Utc::now();
+        let mut clip = clip("public");
+        assert_eq!(cache_control(&clip, now), "public, max-age=86400");
+
+        clip.expires_at = Some(now + Duration::seconds(90));
+        assert_eq!(cache_control(&clip, now), "public, max-age=90");
+        clip.expires_at = Some(now);
+        assert_eq!(cache_control(&clip, now), "private, no-store");
+        clip.expires_at = None;
+
+        clip.max_views = Some(1);
+        assert_eq!(cache_control(&clip, now), "private, no-store");
+        clip.max_views = None;
+
+        for access_type in ["unlisted", "private"] {
+            clip.access_type = access_type.to_string();
+            assert_eq!(cache_control(&clip, now), "private, no-store");
+        }
+    }
+}
@@ -0,0 +1,484 @@
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method};
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, RandomState},
+    net::{IpAddr, Ipv6Addr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::config::Config;
+
+/// 令牌桶按分钟补充
+const WINDOW_SECONDS: f64 = 60.0;
+/// 令牌桶分片数，同一主体的令牌桶落在同一分片
+const SHARDS: usize = 16;
+/// 定期清理已补满令牌桶的间隔
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+/// 分片已满时两次清理的最小间隔，避免每个请求都遍历整个分片
+const FULL_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 需要单独限流的路由分组
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteGroup {
+    /// 注册、登录、刷新令牌、修改密码
+    Auth,
+    /// 新建、修改、删除 Clip 及回收站操作
+    Write,
+    /// 分享链接、原文与二维码
+    Share,
+}
+
+impl RouteGroup {
+    /// 按请求方法与路径归类，未归类的请求只受全局策略限制
+    pub fn classify(method: &Method, path: &str) -> Option<Self> {
+        if matches!(path, "/api/auth/register" | "/api/auth/login" | "/api/auth/refresh" | "/api/auth/password") {
+            return Some(RouteGroup::Auth);
+        }
+        if path.starts_with("/s/") || path.starts_with("/r/") {
+            return Some(RouteGroup::Share);
+        }
+        if method != Method::GET && (path.starts_with("/api/clips") || path.starts_with("/api/trash")) {
+            return Some(RouteGroup::Write);
+        }
+        None
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            RouteGroup::Auth => "auth",
+            RouteGroup::Write => "write",
+            RouteGroup::Share => "share",
+        }
+    }
+}
+
+/// 限流主体：已登录按用户计数，匿名按 IP 计数（IPv6 按 /64 前缀计数）
+pub enum Subject {
+    User(i64),
+    Ip(String),
+}
+
+impl Subject {
+    fn key(&self) -> String {
+        match self {
+            Subject::User(user_id) => format!("user:{}", user_id),
+            Subject::Ip(ip) => format!("ip:{}", ip_prefix(ip)),
+        }
+    }
+}
+
+/// IPv6 地址取 /64 前缀：一个客户端通常拥有整个 /64，逐个换地址不能绕过限流，也不能占满令牌桶
+fn ip_prefix(ip: &str) -> String {
+    match ip.parse::<IpAddr>().map(|ip| ip.to_canonical()) {
+        Ok(IpAddr::V6(ip)) => {
+            let prefix = Ipv6Addr::from(ip.to_bits() & (u128::MAX << 64));
+            format!("{}/64", prefix)
+        }
+        Ok(ip) => ip.to_string(),
+        Err(_) => ip.to_string(),
+    }
+}
+
+/// 一次限流判定的结果，取所有相关令牌桶中最紧张的一个
+#[derive(Debug)]
+pub struct Decision {
+    /// 每分钟上限，即令牌桶容量
+    pub limit: u32,
+    /// 剩余可用请求数
+    pub remaining: u32,
+    /// 令牌桶补满所需秒数
+    pub reset: u64,
+    /// 被拒绝时下一个令牌可用前需等待的秒数
+    pub retry_after: Option<u64>,
+}
+
+impl Decision {
+    /// 写入 RateLimit-Limit、RateLimit-Remaining、RateLimit-Reset、RateLimit-Policy 响应头
+    pub fn write_headers(&self, headers: &mut HeaderMap) {
+        let values = [
+            ("ratelimit-limit", self.limit.to_string()),
+            ("ratelimit-remaining", self.remaining.to_string()),
+            ("ratelimit-reset", self.reset.to_string()),
+            ("ratelimit-policy", format!("{};w={}", self.limit, WINDOW_SECONDS as u64)),
+        ];
+
+        for (name, value) in values {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                headers.insert(HeaderName::from_static(name), value);
+            }
+        }
+    }
+}
+
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    /// now 时刻可用的令牌数
+    fn tokens_at(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        (self.tokens + elapsed * self.capacity / WINDOW_SECONDS).min(self.capacity)
+    }
+
+    fn refill(&mut self, now: Instant) {
+        self.tokens = self.tokens_at(now);
+        self.updated_at = now;
+    }
+
+    /// 补充 amount 个令牌所需秒数
+    fn seconds_for(&self, amount: f64) -> u64 {
+        (amount.max(0.0) * WINDOW_SECONDS / self.capacity).ceil() as u64
+    }
+}
+
+struct Shard {
+    buckets: HashMap<String, Bucket>,
+    swept_at: Instant,
+}
+
+impl Shard {
+    /// 丢弃已补满的令牌桶：补满的桶与新建的桶等价，丢弃不会放宽限制
+    fn sweep(&mut self, now: Instant) {
+        self.buckets.retain(|_, bucket| bucket.tokens_at(now) < bucket.capacity);
+        self.swept_at = now;
+    }
+
+    /// 淘汰 count 个剩余令牌比例最高的桶，比例相同时先淘汰最久未补充的，keep 中的桶不淘汰
+    ///
+    /// 剩余越多的桶越接近补满，淘汰后重建对限制的放宽也最少。
+    fn evict(&mut self, count: usize, now: Instant, keep: &[(String, u32)]) {
+        let mut candidates: Vec<(f64, Instant, &String)> = self.buckets.iter()
+            .filter(|(key, _)| !keep.iter().any(|(kept, _)| kept == *key))
+            .map(|(key, bucket)| (bucket.tokens_at(now) / bucket.capacity, bucket.updated_at, key))
+            .collect();
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        let evicted: Vec<String> = candidates.into_iter()
+            .take(count)
+            .map(|(_, _, key)| key.clone())
+            .collect();
+        for key in evicted {
+            self.buckets.remove(&key);
+        }
+    }
+}
+
+/// 进程内的令牌桶限流器
+///
+/// 每个主体有一个全局令牌桶，命中路由分组时再额外消耗该分组的令牌桶；
+/// 另外每个客户端 IP 还有一个在认证之前检查的令牌桶，不论请求是否登录。
+/// 令牌桶按主体分片存放，各分片定期清理已补满的桶；分片清理后仍然放不下时，
+/// 淘汰剩余令牌比例最高的桶为新主体腾出位置，而不是拒绝新主体，
+/// 否则大量不同地址的请求就能让所有新客户端都被限流。
+#[derive(Clone)]
+pub struct RateLimiter {
+    anonymous_per_minute: u32,
+    user_per_minute: u32,
+    auth_per_minute: u32,
+    write_per_minute: u32,
+    share_per_minute: u32,
+    ip_per_minute: u32,
+    /// 每个分片最多保留的令牌桶数量
+    shard_capacity: usize,
+    hasher: RandomState,
+    shards: Arc<[Mutex<Shard>]>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            anonymous_per_minute: config.rate_limit_anonymous_per_minute,
+            user_per_minute: config.rate_limit_user_per_minute,
+            auth_per_minute: config.rate_limit_auth_per_minute,
+            write_per_minute: config.rate_limit_write_per_minute,
+            share_per_minute: config.rate_limit_share_per_minute,
+            ip_per_minute: config.rate_limit_ip_per_minute,
+            shard_capacity: (config.rate_limit_max_entries / SHARDS).max(2),
+            hasher: RandomState::new(),
+            shards: new_shards(),
+        }
+    }
+
+    /// 消耗一个令牌；所有相关令牌桶都有余量时才放行，策略都未启用时返回 None
+    pub fn check(&self, subject: &Subject, group: Option<RouteGroup>) -> Option<Decision> {
+        let subject_key = subject.key();
+        let global_limit = match subject {
+            Subject::User(_) => self.user_per_minute,
+            Subject::Ip(_) => self.anonymous_per_minute,
+        };
+
+        let mut limits = vec![(format!("all:{}", subject_key), global_limit)];
+        if let Some(group) = group {
+            limits.push((format!("{}:{}", group.name(), subject_key), self.group_limit(group)));
+        }
+        self.consume(&subject_key, limits)
+    }
+
+    /// 认证之前按客户端 IP 消耗一个令牌，未启用时返回 None
+    pub fn check_ip(&self, ip: &str) -> Option<Decision> {
+        let subject_key = Subject::Ip(ip.to_string()).key();
+        let limits = vec![(format!("any:{}", subject_key), self.ip_per_minute)];
+
+        self.consume(&subject_key, limits)
+    }
+
+    /// 在主体所在分片中消耗 limits 对应的令牌桶
+    fn consume(&self, subject_key: &str, mut limits: Vec<(String, u32)>) -> Option<Decision> {
+        limits.retain(|(_, limit)| *limit > 0);
+        if limits.is_empty() {
+            return None;
+        }
+
+        let now = Instant::now();
+        let shard = &self.shards[self.hasher.hash_one(subject_key) as usize % SHARDS];
+        let mut shard = shard.lock().unwrap_or_else(|e| e.into_inner());
+
+        if now.saturating_duration_since(shard.swept_at) >= SWEEP_INTERVAL {
+            shard.sweep(now);
+        }
+
+        let missing = limits.iter().filter(|(key, _)| !shard.buckets.contains_key(key)).count();
+        if missing > 0 && shard.buckets.len() + missing > self.shard_capacity {
+            if now.saturating_duration_since(shard.swept_at) >= FULL_SWEEP_INTERVAL {
+                shard.sweep(now);
+            }
+            let excess = (shard.buckets.len() + missing).saturating_sub(self.shard_capacity);
+            if excess > 0 {
+                tracing::warn!("限流令牌桶已满，为新主体 {} 淘汰 {} 个令牌桶", subject_key, excess);
+                shard.evict(excess, now, &limits);
+            }
+        }
+
+        let buckets = &mut shard.buckets;
+        for (key, limit) in &limits {
+            let bucket = buckets.entry(key.clone())
+                .or_insert(Bucket { capacity: *limit as f64, tokens: *limit as f64, updated_at: now });
+            bucket.capacity = *limit as f64;
+            bucket.refill(now);
+        }
+
+        let allowed = limits.iter().all(|(key, _)| buckets[key].tokens >= 1.0);
+        let retry_after = (!allowed).then(|| {
+            limits.iter()
+                .map(|(key, _)| buckets[key].seconds_for(1.0 - buckets[key].tokens))
+                .max()
+                .unwrap_or_default()
+                .max(1)
+        });
+
+        let mut decision: Option<Decision> = None;
+        for (key, limit) in &limits {
+            let Some(bucket) = buckets.get_mut(key) else {
+                continue;
+            };
+            if allowed {
+                bucket.tokens -= 1.0;
+            }
+
+            let candidate = Decision {
+                limit: *limit,
+                remaining: bucket.tokens.floor() as u32,
+                reset: bucket.seconds_for(bucket.capacity - bucket.tokens),
+                retry_after,
+            };
+            let tighter = decision.as_ref()
+                .is_none_or(|current| (candidate.remaining, candidate.limit) < (current.remaining, current.limit));
+            if tighter {
+                decision = Some(candidate);
+            }
+        }
+
+        decision
+    }
+
+    fn group_limit(&self, group: RouteGroup) -> u32 {
+        match group {
+            RouteGroup::Auth => self.auth_per_minute,
+            RouteGroup::Write => self.write_per_minute,
+            RouteGroup::Share => self.share_per_minute,
+        }
+    }
+}
+
+fn new_shards() -> Arc<[Mutex<Shard>]> {
+    let now = Instant::now();
+    (0..SHARDS)
+        .map(|_| Mutex::new(Shard { buckets: HashMap::new(), swept_at: now }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(anonymous_per_minute: u32, auth_per_minute: u32, shard_capacity: usize) -> RateLimiter {
+        RateLimiter {
+            anonymous_per_minute,
+            user_per_minute: 100,
+            auth_per_minute,
+            write_per_minute: 0,
+            share_per_minute: 0,
+            ip_per_minute: 4,
+            shard_capacity,
+            hasher: RandomState::new(),
+            shards: new_shards(),
+        }
+    }
+
+    /// 找出 count 个落在同一分片的 IP
+    fn same_shard_ips(limiter: &RateLimiter, count: usize) -> Vec<String> {
+        let mut by_shard: HashMap<usize, Vec<String>> = HashMap::new();
+        for i in 0.. {
+            let ip = format!("10.0.0.{}", i);
+            let shard = limiter.hasher.hash_one(Subject::Ip(ip.clone()).key()) as usize % SHARDS;
+            let ips = by_shard.entry(shard).or_default();
+            ips.push(ip);
+            if ips.len() == count {
+                return ips.clone();
+            }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn classify_route_groups() {
+        assert_eq!(RouteGroup::classify(&Method::POST, "/api/auth/login"), Some(RouteGroup::Auth));
+        assert_eq!(RouteGroup::classify(&Method::POST, "/api/auth/password"), Some(RouteGroup::Auth));
+        assert_eq!(RouteGroup::classify(&Method::POST, "/api/clips"), Some(RouteGroup::Write));
+        assert_eq!(RouteGroup::classify(&Method::DELETE, "/api/trash/1"), Some(RouteGroup::Write));
+        assert_eq!(RouteGroup::classify(&Method::GET, "/api/clips"), None);
+        assert_eq!(RouteGroup::classify(&Method::GET, "/s/abc/qr.svg"), Some(RouteGroup::Share));
+    }
+
+    #[test]
+    fn check_rejects_after_limit() {
+        let limiter = limiter(3, 0, 100);
+        let subject = Subject::Ip("10.0.0.1".to_string());
+
+        let remaining: Vec<u32> = (0..3)
+            .map(|_| limiter.check(&subject, None).unwrap())
+            .inspect(|decision| assert!(decision.retry_after.is_none()))
+            .map(|decision| decision.remaining)
+            .collect();
+        assert_eq!(remaining, [2, 1, 0]);
+
+        let rejected = limiter.check(&subject, None).unwrap();
+        assert_eq!(rejected.limit, 3);
+        assert!(rejected.retry_after.is_some_and(|seconds| seconds >= 1));
+
+        // 其他主体不受影响
+        let other = limiter.check(&Subject::Ip("10.0.0.2".to_string()), None).unwrap();
+        assert_eq!(other.remaining, 2);
+    }
+
+    #[test]
+    fn check_reports_tightest_bucket() {
+        let limiter = limiter(10, 2, 100);
+        let subject = Subject::Ip("10.0.0.1".to_string());
+
+        let decision = limiter.check(&subject, Some(RouteGroup::Auth)).unwrap();
+        assert_eq!((decision.limit, decision.remaining), (2, 1));
+        limiter.check(&subject, Some(RouteGroup::Auth)).unwrap();
+        assert!(limiter.check(&subject, Some(RouteGroup::Auth)).unwrap().retry_after.is_some());
+
+        // 分组额度用完不影响其他请求，被拒绝的请求也不消耗全局额度
+        let decision = limiter.check(&subject, None).unwrap();
+        assert_eq!((decision.limit, decision.remaining, decision.retry_after), (10, 7, None));
+    }
+
+    #[test]
+    fn check_ip_counts_separately_from_subject_buckets() {
+        let limiter = limiter(10, 0, 100);
+        let subject = Subject::Ip("10.0.0.1".to_string());
+
+        for _ in 0..4 {
+            assert!(limiter.check_ip("10.0.0.1").unwrap().retry_after.is_none());
+        }
+        assert!(limiter.check_ip("10.0.0.1").unwrap().retry_after.is_some());
+        assert_eq!(limiter.check(&subject, None).unwrap().remaining, 9);
+    }
+
+    #[test]
+    fn check_without_limits_returns_none() {
+        let limiter = limiter(0, 0, 100);
+        assert!(limiter.check(&Subject::Ip("10.0.0.1".to_string()), Some(RouteGroup::Auth)).is_none());
+    }
+
+    #[test]
+    fn ipv6_subjects_share_a_64_prefix() {
+        assert_eq!(Subject::Ip("2001:db8:1:2:aaaa::1".to_string()).key(), "ip:2001:db8:1:2::/64");
+        assert_eq!(Subject::Ip("2001:db8:1:2:bbbb::2".to_string()).key(), "ip:2001:db8:1:2::/64");
+        assert_eq!(Subject::Ip("::ffff:10.0.0.1".to_string()).key(), "ip:10.0.0.1");
+        assert_eq!(Subject::Ip("unknown".to_string()).key(), "ip:unknown");
+
+        let limiter = limiter(10, 0, 100);
+        for i in 0..4 {
+            assert!(limiter.check_ip(&format!("2001:db8::{:x}", i)).unwrap().retry_after.is_none());
+        }
+        assert!(limiter.check_ip("2001:db8::ffff").unwrap().retry_after.is_some());
+        assert!(limiter.check_ip("2001:db8:0:1::1").unwrap().retry_after.is_none());
+    }
+
+    #[test]
+    fn full_shard_evicts_fullest_bucket() {
+        let limiter = limiter(5, 0, 2);
+        let ips = same_shard_ips(&limiter, 3);
+
+        for _ in 0..3 {
+            limiter.check(&Subject::Ip(ips[0].clone()), None).unwrap();
+        }
+        limiter.check(&Subject::Ip(ips[1].clone()), None).unwrap();
+
+        // 分片已满且没有可清理的桶时淘汰剩余最多的桶，新主体照常放行
+        let decision = limiter.check(&Subject::Ip(ips[2].clone()), None).unwrap();
+        assert_eq!((decision.remaining, decision.retry_after), (4, None));
+
+        // 消耗较多的主体的额度没有被重置
+        let decision = limiter.check(&Subject::Ip(ips[0].clone()), None).unwrap();
+        assert_eq!(decision.remaining, 1);
+    }
+
+    #[test]
+    fn address_flood_does_not_lock_out_new_clients() {
+        let limiter = limiter(5, 0, 2);
+
+        for i in 0..10_000u32 {
+            let ip = std::net::Ipv4Addr::from(0x0a00_0000 + i).to_string();
+            for _ in 0..5 {
+                limiter.check(&Subject::Ip(ip.clone()), None);
+            }
+            limiter.check_ip(&ip);
+        }
+
+        let decision = limiter.check(&Subject::Ip("192.0.2.1".to_string()), None).unwrap();
+        assert_eq!((decision.remaining, decision.retry_after), (4, None));
+        let decision = limiter.check_ip("192.0.2.1").unwrap();
+        assert_eq!((decision.remaining, decision.retry_after), (3, None));
+    }
+
+    #[test]
+    fn full_shard_sweeps_refilled_buckets() {
+        let limiter = limiter(5, 0, 2);
+        let ips = same_shard_ips(&limiter, 3);
+
+        limiter.check(&Subject::Ip(ips[0].clone()), None).unwrap();
+        limiter.check(&Subject::Ip(ips[1].clone()), None).unwrap();
+
+        // 模拟两个桶都已补满，且距上次清理已超过最小间隔
+        let shard = limiter.hasher.hash_one(Subject::Ip(ips[0].clone()).key()) as usize % SHARDS;
+        {
+            let mut shard = limiter.shards[shard].lock().unwrap();
+            shard.swept_at = Instant::now() - FULL_SWEEP_INTERVAL;
+            for bucket in shard.buckets.values_mut() {
+                bucket.tokens = bucket.capacity;
+            }
+        }
+
+        let decision = limiter.check(&Subject::Ip(ips[2].clone()), None).unwrap();
+        assert_eq!((decision.remaining, decision.retry_after), (4, None));
+    }
+}
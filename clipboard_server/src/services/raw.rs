@@ -0,0 +1,201 @@
+use chrono::{DateTime, Utc};
+
+use crate::database::models::ClipContent;
+
+/// 未设置过期时间的 Clip 的缓存时长（秒），内容可能被修改，不宜缓存太久
+const MAX_AGE_SECONDS: i64 = 300;
+/// 下载文件名（不含扩展名）的最大字符数
+const MAX_FILE_STEM_CHARS: usize = 100;
+
+/// 根据 content_type / language 推断正文的媒体类型
+///
+/// 能在浏览器中执行脚本的类型（html、svg、js 等）一律按纯文本返回。
+pub fn media_type(clip: &ClipContent) -> &'static str {
+    match clip.content_type.as_str() {
+        "markdown" => "text/markdown; charset=utf-8",
+        "code" => match language(clip).as_str() {
+            "json" => "application/json; charset=utf-8",
+            "yaml" | "yml" => "application/yaml; charset=utf-8",
+            "toml" => "application/toml; charset=utf-8",
+            "csv" => "text/csv; charset=utf-8",
+            "markdown" | "md" => "text/markdown; charset=utf-8",
+            _ => "text/plain; charset=utf-8",
+        },
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+/// Content-Disposition 头：文件名取标题（无标题时取短链接），扩展名由类型推断
+///
+/// 同时给出 ASCII 回退的 filename 与 RFC 5987 编码的 filename*，以支持中文标题。
+pub fn content_disposition(clip: &ClipContent) -> String {
+    let extension = extension(clip);
+    let stem: String = clip.title.as_deref()
+        .unwrap_or("")
+        .chars()
+        .map(|c| if c.is_control() || matches!(c, '/' | '\\' | '"' | ':' | '*' | '?' | '<' | '>' | '|') { '_' } else { c })
+        .take(MAX_FILE_STEM_CHARS)
+        .collect::<String>()
+        .trim_matches(|c: char| c.is_whitespace() || c == '.')
+        .to_string();
+    let stem = if stem.is_empty() {
+        clip.short_url.clone().unwrap_or_else(|| format!("clip-{}", clip.id))
+    } else {
+        stem
+    };
+
+    let file_name = if stem.to_lowercase().ends_with(&format!(".{}", extension)) {
+        stem
+    } else {
+        format!("{}.{}", stem, extension)
+    };
+    let ascii_name: String = file_name.chars()
+        .map(|c| if c.is_ascii_graphic() || c == ' ' { c } else { '_' })
+        .collect();
+
+    format!("inline; filename=\"{}\"; filename*=UTF-8''{}", ascii_name, percent_encode(&file_name))
+}
+
+/// Cache-Control 头：有过期时间时最多缓存到过期为止，限制查看次数的 Clip 不允许缓存
+pub fn cache_control(clip: &ClipContent, now: DateTime<Utc>) -> String {
+    if clip.max_views.is_some() {
+        return "no-store".to_string();
+    }
+
+    let max_age = clip.expires_at
+        .map(|expires_at| (expires_at - now).num_seconds().clamp(0, MAX_AGE_SECONDS))
+        .unwrap_or(MAX_AGE_SECONDS);
+    if max_age == 0 {
+        return "no-store".to_string();
+    }
+
+    // 非公开 Clip 只允许浏览器缓存，不允许 CDN 等共享缓存保存
+    let scope = if clip.access_type == "public" { "public" } else { "private" };
+    format!("{}, max-age={}", scope, max_age)
+}
+
+/// 下载文件的扩展名
+fn extension(clip: &ClipContent) -> String {
+    match clip.content_type.as_str() {
+        "markdown" => "md".to_string(),
+        "code" => match language(clip).as_str() {
+            "" | "text" | "plaintext" => "txt".to_string(),
+            "javascript" | "js" => "js".to_string(),
+            "typescript" | "ts" => "ts".to_string(),
+            "python" | "py" => "py".to_string(),
+            "rust" | "rs" => "rs".to_string(),
+            "markdown" | "md" => "md".to_string(),
+            "shell" | "bash" | "sh" => "sh".to_string(),
+            "yaml" | "yml" => "yaml".to_string(),
+            "c++" | "cpp" => "cpp".to_string(),
+            "c#" | "csharp" => "cs".to_string(),
+            "golang" | "go" => "go".to_string(),
+            "kotlin" | "kt" => "kt".to_string(),
+            "ruby" | "rb" => "rb".to_string(),
+            // 其余语言名本身即可作为扩展名（json、html、sql、java 等）
+            language if language.chars().all(|c| c.is_ascii_alphanumeric()) && language.len() <= 10 => language.to_string(),
+            _ => "txt".to_string(),
+        },
+        _ => "txt".to_string(),
+    }
+}
+
+fn language(clip: &ClipContent) -> String {
+    clip.language.as_deref().unwrap_or("").trim().to_lowercase()
+}
+
+/// RFC 5987 编码：保留字母数字及少量符号，其余字节按 %XX 转义
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len() * 3);
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn clip(content_type: &str, language: Option<&str>, title: Option<&str>) -> ClipContent {
+        ClipContent {
+            id: 7,
+            title: title.map(str::to_string),
+            content_type: content_type.to_string(),
+            language: language.map(str::to_string),
+            access_type: "public".to_string(),
+            ..ClipContent::fixture()
+        }
+    }
+
+    #[test]
+    fn media_type_by_content_type_and_language() {
+        let cases = [
+            ("text", None, "text/plain; charset=utf-8"),
+            ("markdown", None, "text/markdown; charset=utf-8"),
+            ("code", Some(" JSON "), "application/json; charset=utf-8"),
+            ("code", Some("yml"), "application/yaml; charset=utf-8"),
+            ("code", Some("csv"), "text/csv; charset=utf-8"),
+            ("code", Some("rust"), "text/plain; charset=utf-8"),
+            // 能执行脚本的类型按纯文本返回
+            ("code", Some("html"), "text/plain; charset=utf-8"),
+            ("code", Some("svg"), "text/plain; charset=utf-8"),
+            ("code", Some("javascript"), "text/plain; charset=utf-8"),
+            ("url", None, "text/plain; charset=utf-8"),
+        ];
+
+        for (content_type, language, expected) in cases {
+            assert_eq!(media_type(&clip(content_type, language, None)), expected, "{} {:?}", content_type, language);
+        }
+    }
+
+    #[test]
+    fn content_disposition_file_names() {
+        let cases = [
+            (clip("code", Some("rust"), Some("main")), "inline; filename=\"main.rs\"; filename*=UTF-8''main.rs"),
+            (clip("code", Some("python"), Some("tool.PY")), "inline; filename=\"tool.PY\"; filename*=UTF-8''tool.PY"),
+            (clip("markdown", None, None), "inline; filename=\"abcd1234.md\"; filename*=UTF-8''abcd1234.md"),
+            (clip("code", Some("<x>"), Some("  ..  ")), "inline; filename=\"abcd1234.txt\"; filename*=UTF-8''abcd1234.txt"),
+            (
+                clip("text", None, Some("a\"b/c\r\nd")),
+                "inline; filename=\"a_b_c__d.txt\"; filename*=UTF-8''a_b_c__d.txt",
+            ),
+            (
+                clip("text", None, Some("笔记 1")),
+                "inline; filename=\"__ 1.txt\"; filename*=UTF-8''%E7%AC%94%E8%AE%B0%201.txt",
+            ),
+        ];
+
+        for (clip, expected) in cases {
+            assert_eq!(content_disposition(&clip), expected);
+        }
+    }
+
+    #[test]
+    fn cache_control_by_visibility_and_expiry() {
+        let now = Utc::now();
+        let mut clip = clip("text", None, None);
+        assert_eq!(cache_control(&clip, now), "public, max-age=300");
+
+        clip.expires_at = Some(now + Duration::seconds(60));
+        assert_eq!(cache_control(&clip, now), "public, max-age=60");
+        clip.expires_at = Some(now - Duration::seconds(1));
+        assert_eq!(cache_control(&clip, now), "no-store");
+        clip.expires_at = None;
+
+        // 非公开 Clip 不允许共享缓存
+        for access_type in ["unlisted", "private"] {
+            clip.access_type = access_type.to_string();
+            assert_eq!(cache_control(&clip, now), "private, max-age=300");
+        }
+
+        clip.access_type = "public".to_string();
+        clip.max_views = Some(3);
+        assert_eq!(cache_control(&clip, now), "no-store");
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::{Duration, Utc};
+use std::sync::{Arc, Mutex};
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+use super::auth::LOGIN_FAILURE_WINDOW_HOURS;
+use crate::{
+    config::Config,
+    database::{models::JobStatus, DbPool},
+};
+
+/// 任务锁的有效期比执行间隔短这么多秒，持有锁的实例下一轮总能续期
+const LOCK_SLACK_SECONDS: i64 = 5;
+
+/// 后台定时任务
+#[derive(Debug, Clone, Copy)]
+enum Job {
+    /// 彻底删除已过期的 Clips 并写入墓碑
+    ExpiredClips,
+    /// 彻底删除在回收站中超过保留天数的 Clips
+    Trash,
+    /// 删除已过期的会话
+    Sessions,
+    /// 删除超过保留天数的访问日志
+    AccessLogs,
+    /// 删除统计窗口外且未处于锁定中的登录失败计数
+    LoginAttempts,
+    /// 删除超过保留天数的删除墓碑
+    Tombstones,
+}
+
+impl Job {
+    const ALL: [Job; 6] = [Job::ExpiredClips, Job::Trash, Job::Sessions, Job::AccessLogs, Job::LoginAttempts, Job::Tombstones];
+
+    fn name(self) -> &'static str {
+        match self {
+            Job::ExpiredClips => "expired_clips",
+            Job::Trash => "trash",
+            Job::Sessions => "sessions",
+            Job::AccessLogs => "access_logs",
+            Job::LoginAttempts => "login_attempts",
+            Job::Tombstones => "tombstones",
+        }
+    }
+
+    fn interval(self) -> Duration {
+        match self {
+            Job::ExpiredClips => Duration::minutes(5),
+            Job::Trash | Job::Sessions | Job::LoginAttempts => Duration::hours(1),
+            Job::AccessLogs | Job::Tombstones => Duration::days(1),
+        }
+    }
+}
+
+/// 进程内的定时任务调度器
+///
+/// 每个任务执行前先在数据库中抢占任务锁，多实例部署时同一任务在一个执行周期内只由一个实例执行。
+#[derive(Clone)]
+pub struct Scheduler {
+    pool: DbPool,
+    config: Config,
+    /// 本实例的标识，作为任务锁的持有者
+    instance_id: String,
+    statuses: Arc<Mutex<Vec<JobStatus>>>,
+}
+
+impl Scheduler {
+    pub fn new(pool: DbPool, config: Config) -> Self {
+        let statuses = Job::ALL.iter()
+            .map(|job| JobStatus {
+                name: job.name().to_string(),
+                interval_seconds: job.interval().num_seconds(),
+                running: false,
+                last_started_at: None,
+                last_finished_at: None,
+                last_affected: None,
+                last_error: None,
+                last_skipped_at: None,
+                next_run_at: None,
+            })
+            .collect();
+
+        Self {
+            pool,
+            config,
+            instance_id: Uuid::new_v4().to_string(),
+            statuses: Arc::new(Mutex::new(statuses)),
+        }
+    }
+
+    /// 为每个任务启动后台循环，启动后立即执行一次
+    pub fn start(&self) {
+        for job in Job::ALL {
+            let scheduler = self.clone();
+            tokio::spawn(async move {
+                let period = job.interval().to_std().unwrap_or_default();
+                let mut interval = tokio::time::interval(period);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                loop {
+                    interval.tick().await;
+                    scheduler.run(job).await;
+                }
+            });
+        }
+    }
+
+    /// 所有任务的运行状态
+    pub fn statuses(&self) -> Vec<JobStatus> {
+        self.lock_statuses().clone()
+    }
+
+    async fn run(&self, job: Job) {
+        let now = Utc::now();
+        let next_run_at = now + job.interval();
+        let lock_until = next_run_at - Duration::seconds(LOCK_SLACK_SECONDS);
+
+        match self.pool.jobs().try_lock(job.name(), &self.instance_id, lock_until).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.update(job, |status| {
+                    status.last_skipped_at = Some(now);
+                    status.next_run_at = Some(next_run_at);
+                });
+                return;
+            }
+            Err(e) => {
+                tracing::error!("定时任务 {} 获取锁失败: {}", job.name(), e);
+                self.update(job, |status| {
+                    status.last_error = Some(format!("获取锁失败: {}", e));
+                    status.next_run_at = Some(next_run_at);
+                });
+                return;
+            }
+        }
+
+        self.update(job, |status| {
+            status.running = true;
+            status.last_started_at = Some(now);
+        });
+
+        let clips = self.pool.clips();
+        let result = match job {
+            Job::ExpiredClips => clips.delete_expired_clips(now).await,
+            Job::Trash => clips.purge_trash(now - Duration::days(self.config.trash_retention_days)).await,
+            Job::Sessions => self.pool.sessions().delete_expired_sessions(now).await,
+            Job::AccessLogs => clips.delete_access_logs(now - Duration::days(self.config.access_log_retention_days)).await,
+            Job::Tombstones => clips.delete_tombstones(now - Duration::days(self.config.sync_tombstone_retention_days)).await,
+            Job::LoginAttempts => {
+                let before = now - Duration::hours(LOGIN_FAILURE_WINDOW_HOURS);
+                self.pool.login_attempts().delete_stale_attempts(before, now).await
+            }
+        };
+
+        match &result {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("定时任务 {} 完成，删除 {} 条记录", job.name(), count),
+            Err(e) => tracing::error!("定时任务 {} 执行失败: {}", job.name(), e),
+        }
+
+        self.update(job, |status| {
+            status.running = false;
+            status.last_finished_at = Some(Utc::now());
+            status.next_run_at = Some(next_run_at);
+            match result {
+                Ok(count) => {
+                    status.last_affected = Some(count);
+                    status.last_error = None;
+                }
+                Err(e) => status.last_error = Some(e.to_string()),
+            }
+        });
+    }
+
+    fn update(&self, job: Job, apply: impl FnOnce(&mut JobStatus)) {
+        if let Some(status) = self.lock_statuses().iter_mut().find(|s| s.name == job.name()) {
+            apply(status);
+        }
+    }
+
+    fn lock_statuses(&self) -> std::sync::MutexGuard<'_, Vec<JobStatus>> {
+        // 持锁期间不会 panic，出现中毒时直接沿用内部数据
+        self.statuses.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,265 @@
+use std::cmp::Ordering;
+
+use crate::database::models::{ClipContent, SearchResult};
+
+/// 单次搜索最多使用的关键词数
+const MAX_TERMS: usize = 8;
+/// 摘要片段长度（字符）
+const SNIPPET_CHARS: usize = 120;
+/// 摘要中首个命中位置之前保留的上下文长度（字符）
+const SNIPPET_CONTEXT: usize = 20;
+/// 标题命中相对正文命中的权重
+const TITLE_WEIGHT: f64 = 3.0;
+
+/// 将搜索词切分为关键词
+///
+/// 字母数字按连续片段成词；中日韩文字没有空格分隔，按相邻两字切分，
+/// 单独出现的一个字保留为一个关键词。结果统一转为小写并去重。
+pub fn tokenize(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut cjk: Vec<char> = Vec::new();
+
+    for c in query.chars().map(fold) {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut terms);
+            cjk.push(c);
+        } else if c.is_alphanumeric() {
+            flush_cjk(&mut cjk, &mut terms);
+            word.push(c);
+        } else {
+            flush_word(&mut word, &mut terms);
+            flush_cjk(&mut cjk, &mut terms);
+        }
+    }
+    flush_word(&mut word, &mut terms);
+    flush_cjk(&mut cjk, &mut terms);
+
+    let mut unique: Vec<String> = Vec::new();
+    for term in terms {
+        if !unique.contains(&term) {
+            unique.push(term);
+        }
+    }
+    unique.truncate(MAX_TERMS);
+    unique
+}
+
+/// 对候选 Clips 打分排序，并生成高亮标题与摘要
+pub fn rank(clips: Vec<ClipContent>, terms: &[String]) -> Vec<SearchResult> {
+    let terms: Vec<Vec<char>> = terms.iter().map(|t| t.chars().collect()).collect();
+
+    let mut results: Vec<SearchResult> = clips.into_iter()
+        .map(|clip| {
+            let title: Vec<char> = clip.title.as_deref().unwrap_or("").chars().collect();
+            let content: Vec<char> = clip.content.chars().collect();
+            let title_matches = find_matches(&title, &terms);
+            let content_matches = find_matches(&content, &terms);
+
+            // 标题命中加权；正文命中次数取对数，避免长文本刷分
+            let score = title_matches.iter().zip(&content_matches)
+                .map(|(in_title, in_content)| {
+                    let title_hit = if in_title.is_empty() { 0.0 } else { TITLE_WEIGHT };
+                    title_hit + (1.0 + in_content.len() as f64).ln()
+                })
+                .sum();
+
+            let title_highlight = clip.title.as_ref()
+                .map(|_| highlight(&title, &merge(title_matches)));
+            let snippet = snippet(&content, &merge(content_matches));
+
+            SearchResult { clip, score, title_highlight, snippet }
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        b.score.partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then(b.clip.updated_at.cmp(&a.clip.updated_at))
+    });
+    results
+}
+
+/// 逐个关键词查找命中区间 [start, end)（按字符计，忽略大小写）
+fn find_matches(text: &[char], terms: &[Vec<char>]) -> Vec<Vec<(usize, usize)>> {
+    let folded: Vec<char> = text.iter().copied().map(fold).collect();
+
+    terms.iter()
+        .map(|term| {
+            if term.is_empty() || term.len() > folded.len() {
+                return Vec::new();
+            }
+            folded.windows(term.len())
+                .enumerate()
+                .filter(|(_, window)| *window == &term[..])
+                .map(|(start, _)| (start, start + term.len()))
+                .collect()
+        })
+        .collect()
+}
+
+/// 合并所有关键词的命中区间，重叠或相邻的区间合为一个
+fn merge(matches: Vec<Vec<(usize, usize)>>) -> Vec<(usize, usize)> {
+    let mut matches: Vec<(usize, usize)> = matches.into_iter().flatten().collect();
+    matches.sort_unstable();
+
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in matches {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// 截取首个命中位置附近的正文片段并高亮
+fn snippet(content: &[char], matches: &[(usize, usize)]) -> String {
+    let start = matches.first()
+        .map(|m| m.0.saturating_sub(SNIPPET_CONTEXT))
+        .unwrap_or(0);
+    let end = (start + SNIPPET_CHARS).min(content.len());
+
+    // 片段内的命中区间，超出窗口的部分截断
+    let window: Vec<(usize, usize)> = matches.iter()
+        .filter(|m| m.0 < end)
+        .map(|m| (m.0 - start, m.1.min(end) - start))
+        .collect();
+
+    // 换行等空白折叠为空格，保持摘要单行
+    let text: Vec<char> = content[start..end].iter()
+        .map(|&c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    snippet.push_str(&highlight(&text, &window));
+    if end < content.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// HTML 转义文本，并用 <mark> 包裹命中区间
+fn highlight(text: &[char], matches: &[(usize, usize)]) -> String {
+    let mut html = String::with_capacity(text.len() * 2);
+    let mut pos = 0;
+
+    for &(start, end) in matches {
+        push_escaped(&mut html, &text[pos..start]);
+        html.push_str("<mark>");
+        push_escaped(&mut html, &text[start..end]);
+        html.push_str("</mark>");
+        pos = end;
+    }
+    push_escaped(&mut html, &text[pos..]);
+    html
+}
+
+fn push_escaped(html: &mut String, text: &[char]) {
+    for &c in text {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            _ => html.push(c),
+        }
+    }
+}
+
+/// 逐字符转小写，保证折叠前后字符位置一一对应
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'     // 平假名、片假名
+        | '\u{3400}'..='\u{4DBF}'   // CJK 扩展 A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK 基本汉字
+        | '\u{AC00}'..='\u{D7AF}'   // 韩文音节
+        | '\u{F900}'..='\u{FAFF}'   // CJK 兼容汉字
+        | '\u{20000}'..='\u{2A6DF}' // CJK 扩展 B
+    )
+}
+
+fn flush_word(word: &mut String, terms: &mut Vec<String>) {
+    if !word.is_empty() {
+        terms.push(std::mem::take(word));
+    }
+}
+
+fn flush_cjk(cjk: &mut Vec<char>, terms: &mut Vec<String>) {
+    match cjk.len() {
+        0 => {}
+        1 => terms.push(cjk[0].to_string()),
+        _ => terms.extend(cjk.windows(2).map(|pair| pair.iter().collect::<String>())),
+    }
+    cjk.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn clip(id: i64, title: Option<&str>, content: &str) -> ClipContent {
+        let now = Utc::now() - Duration::minutes(id);
+        ClipContent {
+            id,
+            title: title.map(str::to_string),
+            content: content.to_string(),
+            short_url: None,
+            created_at: now,
+            updated_at: now,
+            ..ClipContent::fixture()
+        }
+    }
+
+    #[test]
+    fn tokenize_words_and_cjk_bigrams() {
+        assert_eq!(tokenize("Hello, World! hello"), ["hello", "world"]);
+        assert_eq!(tokenize("剪贴板同步"), ["剪贴", "贴板", "板同", "同步"]);
+        assert_eq!(tokenize("rust 代码 a字"), ["rust", "代码", "a", "字"]);
+        assert!(tokenize("  ,.!  ").is_empty());
+    }
+
+    #[test]
+    fn tokenize_keeps_at_most_max_terms() {
+        let terms = tokenize("a b c d e f g h i j");
+        assert_eq!(terms.len(), MAX_TERMS);
+        assert_eq!(terms.last().map(String::as_str), Some("h"));
+    }
+
+    #[test]
+    fn rank_prefers_title_hits() {
+        let terms = tokenize("rust");
+        let results = rank(
+            vec![
+                clip(1, None, "rust rust rust"),
+                clip(2, Some("Rust 笔记"), "notes"),
+                clip(3, None, "nothing here"),
+            ],
+            &terms,
+        );
+
+        let ids: Vec<i64> = results.iter().map(|result| result.clip.id).collect();
+        assert_eq!(ids, [2, 1, 3]);
+        assert_eq!(results[0].title_highlight.as_deref(), Some("<mark>Rust</mark> 笔记"));
+        assert_eq!(results[2].score, 0.0);
+    }
+
+    #[test]
+    fn rank_escapes_and_trims_snippets() {
+        let content = format!("{}<b>needle</b>{}", "x".repeat(50), "y".repeat(200));
+        let results = rank(vec![clip(1, None, &content)], &tokenize("needle"));
+
+        let snippet = &results[0].snippet;
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("&lt;b&gt;<mark>needle</mark>&lt;/b&gt;"));
+    }
+}
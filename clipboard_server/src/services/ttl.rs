@@ -0,0 +1,52 @@
+use chrono::Duration;
+
+/// 相对有效期的上限（约 10 年）
+const MAX_SECONDS: i64 = 3650 * 86400;
+
+/// 解析相对有效期：正整数加单位，s 秒、m 分钟、h 小时、d 天、w 周，如 `10m`、`1d`、`1w`
+pub fn parse(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let unit = value.chars().last()?;
+    let amount: i64 = value[..value.len() - unit.len_utf8()].parse().ok().filter(|n| *n > 0)?;
+
+    let unit_seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        'w' => 7 * 86400,
+        _ => return None,
+    };
+
+    amount.checked_mul(unit_seconds)
+        .filter(|seconds| *seconds <= MAX_SECONDS)
+        .map(Duration::seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_units() {
+        assert_eq!(parse("30s"), Some(Duration::seconds(30)));
+        assert_eq!(parse("10m"), Some(Duration::minutes(10)));
+        assert_eq!(parse(" 2h "), Some(Duration::hours(2)));
+        assert_eq!(parse("1d"), Some(Duration::days(1)));
+        assert_eq!(parse("1w"), Some(Duration::weeks(1)));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_values() {
+        for value in ["", "m", "10", "0m", "-5m", "1.5h", "10M", "1天", "1 d"] {
+            assert_eq!(parse(value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn parse_caps_at_max_seconds() {
+        assert_eq!(parse("3650d"), Some(Duration::days(3650)));
+        assert_eq!(parse("3651d"), None);
+        assert_eq!(parse("9223372036854775807w"), None);
+    }
+}
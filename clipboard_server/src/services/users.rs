@@ -0,0 +1,48 @@
+use super::{ttl, ServiceError};
+use crate::database::{
+    models::{UpdateUserSettings, UserSettings},
+    DbPool,
+};
+
+/// 用户设置业务
+#[derive(Clone)]
+pub struct UserService {
+    pool: DbPool,
+}
+
+impl UserService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 获取用户设置
+    pub async fn settings(&self, user_id: i64) -> Result<UserSettings, ServiceError> {
+        match self.pool.users().find_settings(user_id).await {
+            Ok(settings) => Ok(settings),
+            Err(sqlx::Error::RowNotFound) => Err(ServiceError::not_found("用户不存在")),
+            Err(e) => Err(ServiceError::internal("获取用户设置失败", e)),
+        }
+    }
+
+    /// 修改用户设置，未提供的字段保持不变
+    pub async fn update_settings(&self, user_id: i64, changes: &UpdateUserSettings) -> Result<UserSettings, ServiceError> {
+        let mut settings = self.settings(user_id).await?;
+
+        if let Some(revision_retention) = changes.revision_retention {
+            if !(0..=100).contains(&revision_retention) {
+                return Err(ServiceError::bad_request("历史版本保留数量需在 0-100 之间"));
+            }
+            settings.revision_retention = revision_retention;
+        }
+
+        if let Some(default_expires_in) = &changes.default_expires_in {
+            if default_expires_in.as_deref().is_some_and(|value| ttl::parse(value).is_none()) {
+                return Err(ServiceError::bad_request("默认有效期格式无效，应为数字加单位，如 10m、1d、1w"));
+            }
+            settings.default_expires_in = default_expires_in.as_ref().map(|value| value.trim().to_string());
+        }
+
+        self.pool.users().update_settings(user_id, &settings).await
+            .map_err(|e| ServiceError::internal("保存用户设置失败", e))
+    }
+}
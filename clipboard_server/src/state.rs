@@ -0,0 +1,33 @@
+use axum::extract::FromRef;
+
+use crate::{
+    config::Config,
+    database::DbPool,
+    services::{AuthService, ClipService, RateLimiter, Scheduler, UserService},
+};
+
+/// 路由共享状态，处理器按需提取其中的字段
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub pool: DbPool,
+    pub config: Config,
+    pub auth_service: AuthService,
+    pub clip_service: ClipService,
+    pub user_service: UserService,
+    pub scheduler: Scheduler,
+    pub rate_limiter: RateLimiter,
+}
+
+impl AppState {
+    pub fn new(pool: DbPool, config: Config) -> Self {
+        Self {
+            auth_service: AuthService::new(pool.clone(), config.clone()),
+            clip_service: ClipService::new(pool.clone(), config.clone()),
+            user_service: UserService::new(pool.clone()),
+            scheduler: Scheduler::new(pool.clone(), config.clone()),
+            rate_limiter: RateLimiter::new(&config),
+            pool,
+            config,
+        }
+    }
+}
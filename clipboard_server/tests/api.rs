@@ -0,0 +1,488 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use axum_sqlx_app::{config::{Config, DatabaseBackend}, database::{self, DbPool}, state::AppState};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use sqlx::{Connection, Executor, MySqlConnection, PgConnection};
+use std::{env, sync::Once, time::Duration};
+use tokio::sync::{Mutex, MutexGuard};
+use tower::ServiceExt;
+
+/// 每个用例分别在内存存储与 SQLite 内存库上各运行一次；
+/// 设置 TEST_DATABASE_URL（MySQL/PostgreSQL）时再在该库上串行运行一次，用例开始前会清空该库
+macro_rules! backend_tests {
+    ($($name:ident),* $(,)?) => {
+        mod memory {
+            $(
+                #[tokio::test]
+                async fn $name() {
+                    super::$name("memory://").await;
+                }
+            )*
+        }
+
+        mod sqlite {
+            $(
+                #[tokio::test]
+                async fn $name() {
+                    super::$name("sqlite::memory:").await;
+                }
+            )*
+        }
+
+        mod external {
+            $(
+                #[tokio::test]
+                async fn $name() {
+                    if let Some((_guard, database_url)) = super::external_database().await {
+                        super::$name(&database_url).await;
+                    }
+                }
+            )*
+        }
+    };
+}
+
+backend_tests!(
+    login_is_required_for_clips,
+    usernames_are_case_insensitive,
+    login_success_does_not_reset_ip_failures,
+    create_sync_patch_and_trash,
+    expired_clips_are_hidden_and_purged,
+    list_filters_sort_and_search,
+    put_accepts_relative_expiry,
+    clips_are_private_to_their_owner,
+    share_view_requires_confirmation_for_limited_clips,
+    share_views_do_not_modify_clip,
+    private_clips_are_not_shared,
+);
+
+/// 取得 TEST_DATABASE_URL 指定的外部数据库：持锁期间独占该库，并删除所有表，由自动迁移重新建表
+async fn external_database() -> Option<(MutexGuard<'static, ()>, String)> {
+    static LOCK: Mutex<()> = Mutex::const_new(());
+
+    let database_url = env::var("TEST_DATABASE_URL").ok()?;
+    let guard = LOCK.lock().await;
+
+    match DatabaseBackend::from_url(&database_url) {
+        Some(DatabaseBackend::MySql) => {
+            let mut conn = MySqlConnection::connect(&database_url).await.expect("connect TEST_DATABASE_URL");
+            let tables: Vec<String> = sqlx::query_scalar(
+                "SELECT CAST(table_name AS CHAR) FROM information_schema.tables WHERE table_schema = DATABASE()",
+            )
+            .fetch_all(&mut conn)
+            .await
+            .unwrap();
+            conn.execute("SET FOREIGN_KEY_CHECKS = 0").await.unwrap();
+            for table in tables {
+                conn.execute(format!("DROP TABLE IF EXISTS `{}`", table).as_str()).await.unwrap();
+            }
+        }
+        Some(DatabaseBackend::Postgres) => {
+            let mut conn = PgConnection::connect(&database_url).await.expect("connect TEST_DATABASE_URL");
+            conn.execute("DROP SCHEMA public CASCADE").await.unwrap();
+            conn.execute("CREATE SCHEMA public").await.unwrap();
+        }
+        _ => panic!("TEST_DATABASE_URL 只支持 MySQL 或 PostgreSQL"),
+    }
+
+    Some((guard, database_url))
+}
+
+/// 使用 database_url 指定的存储构建完整路由，每个测试独立一份数据
+async fn app(database_url: &str) -> Router {
+    app_with_pool(database_url).await.0
+}
+
+/// 同 app，同时返回存储，供测试直接执行定时任务对应的操作
+async fn app_with_pool(database_url: &str) -> (Router, DbPool) {
+    app_with_config(database_url, |_| {}).await
+}
+
+/// 同 app_with_pool，构建前先由 configure 调整配置
+async fn app_with_config(database_url: &str, configure: impl FnOnce(&mut Config)) -> (Router, DbPool) {
+    static ENV: Once = Once::new();
+    ENV.call_once(|| {
+        // 已设置的环境变量不会被 .env 覆盖；数据库连接串随后按用例替换
+        env::set_var("DATABASE_URL", "memory://");
+        env::set_var("PUBLIC_BASE_URL", "http://clip.test");
+        for name in [
+            "RATE_LIMIT_ANONYMOUS_PER_MINUTE",
+            "RATE_LIMIT_USER_PER_MINUTE",
+            "RATE_LIMIT_AUTH_PER_MINUTE",
+            "RATE_LIMIT_WRITE_PER_MINUTE",
+            "RATE_LIMIT_SHARE_PER_MINUTE",
+            "RATE_LIMIT_IP_PER_MINUTE",
+        ] {
+            env::set_var(name, "0");
+        }
+    });
+
+    let mut config = Config::from_env();
+    config.database_backend = DatabaseBackend::from_url(database_url).expect("test database backend");
+    config.database_url = database_url.to_string();
+    config.database_auto_migrate = true;
+    configure(&mut config);
+    let pool = database::init_pool(&config).await.expect("test storage");
+
+    (axum_sqlx_app::app(AppState::new(pool.clone(), config)), pool)
+}
+
+/// 发送请求，返回状态码、响应头与 JSON 响应体（非 JSON 时为 Null）
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    headers: &[(&str, &str)],
+    body: Option<Value>,
+) -> (StatusCode, axum::http::HeaderMap, Value) {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let request = match body {
+        Some(body) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+
+    (status, headers, json)
+}
+
+async fn login(app: &Router, username: &str) -> String {
+    let user = json!({ "username": username, "email": format!("{}@example.com", username), "password": "secret1" });
+    let (status, _, _) = send(app, Method::POST, "/api/auth/register", None, &[], Some(user)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let credentials = json!({ "username": username, "password": "secret1" });
+    let (status, _, body) = send(app, Method::POST, "/api/auth/login", None, &[], Some(credentials)).await;
+    assert_eq!(status, StatusCode::OK);
+    body["data"]["access_token"].as_str().unwrap().to_string()
+}
+
+async fn create_clip(app: &Router, token: &str, clip: Value) -> Value {
+    let (status, _, body) = send(app, Method::POST, "/api/clips", Some(token), &[], Some(clip)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    body["data"].clone()
+}
+
+/// 同步只返回一秒之前的变更，等待变更可见
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+}
+
+async fn login_is_required_for_clips(database_url: &str) {
+    let app = app(database_url).await;
+
+    let (status, _, _) = send(&app, Method::GET, "/api/clips", None, &[], None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let credentials = json!({ "username": "nobody", "password": "secret1" });
+    let (status, _, _) = send(&app, Method::POST, "/api/auth/login", None, &[], Some(credentials)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+async fn usernames_are_case_insensitive(database_url: &str) {
+    let app = app(database_url).await;
+
+    let user = json!({ "username": " Alice ", "email": "alice@example.com", "password": "secret1" });
+    let (status, _, body) = send(&app, Method::POST, "/api/auth/register", None, &[], Some(user)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["data"]["username"], "alice");
+    assert!(body["data"].get("password_hash").is_none() && body["data"].get("salt").is_none());
+
+    let duplicate = json!({ "username": "ALICE", "email": "other@example.com", "password": "secret1" });
+    let (status, _, _) = send(&app, Method::POST, "/api/auth/register", None, &[], Some(duplicate)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let credentials = json!({ "username": "aLiCe", "password": "secret1" });
+    let (status, _, _) = send(&app, Method::POST, "/api/auth/login", None, &[], Some(credentials)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+async fn login_success_does_not_reset_ip_failures(database_url: &str) {
+    let (app, _) = app_with_config(database_url, |config| {
+        config.login_ip_max_failures = 3;
+        config.login_max_failures = 100;
+    }).await;
+    login(&app, "alice").await;
+
+    let attempt = |username: &str, password: &str| json!({ "username": username, "password": password });
+    let (status, _, _) = send(&app, Method::POST, "/api/auth/login", None, &[], Some(attempt("bob", "wrong1"))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // 登录自己的账号只撤销本次登记，之前对其他账号的失败仍然计入该 IP
+    let (status, _, _) = send(&app, Method::POST, "/api/auth/login", None, &[], Some(attempt("alice", "secret1"))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _, _) = send(&app, Method::POST, "/api/auth/login", None, &[], Some(attempt("carol", "wrong1"))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, _) = send(&app, Method::POST, "/api/auth/login", None, &[], Some(attempt("dave", "wrong1"))).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let (status, _, _) = send(&app, Method::POST, "/api/auth/login", None, &[], Some(attempt("alice", "secret1"))).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+async fn create_sync_patch_and_trash(database_url: &str) {
+    let app = app(database_url).await;
+    let token = login(&app, "alice").await;
+
+    let clip = create_clip(&app, &token, json!({ "title": "note", "content": "hello" })).await;
+    let id = clip["id"].as_i64().unwrap();
+    assert_eq!(clip["version"], 1);
+    assert!(clip.get("encryption_key").is_none());
+
+    // 首次全量同步
+    settle().await;
+    let (status, _, body) = send(&app, Method::GET, "/api/sync", Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["clips"][0]["id"], id);
+    let cursor = body["data"]["cursor"].as_str().unwrap().to_string();
+
+    // If-Match 与当前版本一致时更新成功，旧版本号返回 409 及当前数据
+    let uri = format!("/api/clips/{}", id);
+    let (status, headers, body) = send(&app, Method::PATCH, &uri, Some(&token), &[("if-match", "\"1\"")], Some(json!({ "content": "hello, world" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[header::ETAG], "\"2\"");
+    assert_eq!(body["data"]["title"], "note");
+
+    let (status, _, body) = send(&app, Method::PATCH, &uri, Some(&token), &[("if-match", "\"1\"")], Some(json!({ "content": "stale" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["data"]["content"], "hello, world");
+
+    // 只支持单个强 ETag：列表与弱 ETag 返回 412，格式错误返回 400
+    for (if_match, expected) in [
+        ("\"2\", \"3\"", StatusCode::PRECONDITION_FAILED),
+        ("W/\"2\"", StatusCode::PRECONDITION_FAILED),
+        ("2", StatusCode::BAD_REQUEST),
+    ] {
+        let (status, _, _) = send(&app, Method::PATCH, &uri, Some(&token), &[("if-match", if_match)], Some(json!({ "content": "stale" }))).await;
+        assert_eq!(status, expected, "If-Match: {}", if_match);
+    }
+
+    // 增量同步只返回游标之后的修改
+    settle().await;
+    let sync_uri = format!("/api/sync?since={}", cursor);
+    let (_, _, body) = send(&app, Method::GET, &sync_uri, Some(&token), &[], None).await;
+    assert_eq!(body["data"]["clips"][0]["version"], 2);
+    assert_eq!(body["data"]["deleted"], json!([]));
+
+    // 没有新变更时游标照样前进，持续轮询的空闲客户端不会因游标过期被迫全量同步
+    let idle_cursor = body["data"]["cursor"].as_str().unwrap().to_string();
+    settle().await;
+    let (_, _, body) = send(&app, Method::GET, &format!("/api/sync?since={}", idle_cursor), Some(&token), &[], None).await;
+    assert_eq!((&body["data"]["clips"], &body["data"]["has_more"]), (&json!([]), &json!(false)));
+    assert_ne!(body["data"]["cursor"], idle_cursor.as_str());
+
+    // 删除后进入回收站，可以恢复
+    let (status, _, _) = send(&app, Method::DELETE, &uri, Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _, _) = send(&app, Method::DELETE, &uri, Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, _, body) = send(&app, Method::GET, "/api/trash", Some(&token), &[], None).await;
+    assert_eq!(body["data"][0]["id"], id);
+
+    let (status, _, _) = send(&app, Method::POST, &format!("/api/trash/{}/restore", id), Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _, _) = send(&app, Method::GET, &uri, Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 彻底删除后，增量同步通过墓碑告知客户端
+    send(&app, Method::DELETE, &uri, Some(&token), &[], None).await;
+    let (status, _, _) = send(&app, Method::DELETE, &format!("/api/trash/{}", id), Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    settle().await;
+    let (_, _, body) = send(&app, Method::GET, &sync_uri, Some(&token), &[], None).await;
+    assert_eq!(body["data"]["clips"], json!([]));
+    assert_eq!(body["data"]["deleted"][0]["id"], id);
+}
+
+async fn expired_clips_are_hidden_and_purged(database_url: &str) {
+    let (app, pool) = app_with_pool(database_url).await;
+    let token = login(&app, "alice").await;
+
+    let kept = create_clip(&app, &token, json!({ "content": "kept" })).await;
+    let expired = create_clip(&app, &token, json!({ "content": "gone", "expires_in": "1s" })).await;
+    settle().await;
+
+    // 默认列表不含已过期的，expired=true 时只看已过期的
+    let (_, _, body) = send(&app, Method::GET, "/api/clips", Some(&token), &[], None).await;
+    assert_eq!(body["data"]["items"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"]["items"][0]["id"], kept["id"]);
+    let (_, _, body) = send(&app, Method::GET, "/api/clips?expired=true", Some(&token), &[], None).await;
+    assert_eq!(body["data"]["items"][0]["id"], expired["id"]);
+    let (_, _, body) = send(&app, Method::GET, "/api/clips/search?q=gone", Some(&token), &[], None).await;
+    assert_eq!(body["data"]["items"], json!([]));
+
+    let (_, _, body) = send(&app, Method::GET, "/api/sync", Some(&token), &[], None).await;
+    let cursor = body["data"]["cursor"].as_str().unwrap().to_string();
+
+    // 定时任务彻底删除过期的 Clip，并通过墓碑告知同步客户端
+    assert_eq!(pool.clips().delete_expired_clips(Utc::now()).await.unwrap(), 1);
+    let (status, _, _) = send(&app, Method::GET, &format!("/api/clips/{}", expired["id"]), Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    settle().await;
+    let (_, _, body) = send(&app, Method::GET, &format!("/api/sync?since={}", cursor), Some(&token), &[], None).await;
+    assert_eq!(body["data"]["deleted"][0]["id"], expired["id"]);
+}
+
+async fn list_filters_sort_and_search(database_url: &str) {
+    let app = app(database_url).await;
+    let token = login(&app, "alice").await;
+
+    create_clip(&app, &token, json!({ "title": "b rust", "content": "fn main() {}", "language": "rust", "tags": ["code"] })).await;
+    create_clip(&app, &token, json!({ "title": "a note", "content": "买牛奶和面包", "tags": ["todo", "home"] })).await;
+    create_clip(&app, &token, json!({ "title": "c secret", "content": "ciphertext", "is_encrypted": true, "tags": ["home"] })).await;
+
+    let titles = |body: &Value| -> Vec<String> {
+        body["data"]["items"].as_array().unwrap().iter().map(|c| c["title"].as_str().unwrap().to_string()).collect()
+    };
+
+    let (_, _, body) = send(&app, Method::GET, "/api/clips?sort=title&order=asc", Some(&token), &[], None).await;
+    assert_eq!(titles(&body), ["a note", "b rust", "c secret"]);
+    assert_eq!(body["data"]["total"], 3);
+
+    let (_, _, body) = send(&app, Method::GET, "/api/clips?tag=home&sort=title&order=desc", Some(&token), &[], None).await;
+    assert_eq!(titles(&body), ["c secret", "a note"]);
+
+    let (_, headers, body) = send(&app, Method::GET, "/api/clips?language=rust&page_size=1", Some(&token), &[], None).await;
+    assert_eq!(titles(&body), ["b rust"]);
+    assert_eq!(body["data"]["has_more"], false);
+    assert!(headers.contains_key(header::LINK));
+
+    // 各后端都按子串匹配：中文单字、大小写不同的关键词同样命中，加密内容不参与搜索
+    for q in ["牛奶", "奶", "MAIN"] {
+        let (_, _, body) = send(&app, Method::GET, &format!("/api/clips/search?q={}", q), Some(&token), &[], None).await;
+        assert_eq!(body["data"]["items"].as_array().unwrap().len(), 1, "{}", q);
+        assert_eq!(body["data"]["truncated"], false);
+    }
+    let (_, _, body) = send(&app, Method::GET, "/api/clips/search?q=ciphertext", Some(&token), &[], None).await;
+    assert_eq!(body["data"]["items"], json!([]));
+}
+
+async fn put_accepts_relative_expiry(database_url: &str) {
+    let app = app(database_url).await;
+    let token = login(&app, "alice").await;
+
+    let clip = create_clip(&app, &token, json!({ "content": "hello", "expires_in": "1d" })).await;
+    let uri = format!("/api/clips/{}", clip["id"]);
+
+    let before = Utc::now();
+    let (status, _, body) = send(&app, Method::PUT, &uri, Some(&token), &[], Some(json!({ "content": "hi", "expires_in": "1h" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let expires_at: DateTime<Utc> = body["data"]["expires_at"].as_str().unwrap().parse().unwrap();
+    assert!(expires_at >= before + chrono::Duration::minutes(59) && expires_at <= Utc::now() + chrono::Duration::hours(1));
+
+    // PUT 整体替换：不给有效期时清空
+    let (_, _, body) = send(&app, Method::PUT, &uri, Some(&token), &[], Some(json!({ "content": "hi" }))).await;
+    assert_eq!(body["data"]["expires_at"], Value::Null);
+
+    let both = json!({ "content": "hi", "expires_in": "1h", "expires_at": "2999-01-01T00:00:00Z" });
+    let (status, _, _) = send(&app, Method::PUT, &uri, Some(&token), &[], Some(both)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+async fn clips_are_private_to_their_owner(database_url: &str) {
+    let app = app(database_url).await;
+    let alice = login(&app, "alice").await;
+    let bob = login(&app, "bob").await;
+
+    let clip = create_clip(&app, &alice, json!({ "content": "mine" })).await;
+    let uri = format!("/api/clips/{}", clip["id"]);
+
+    let (status, _, _) = send(&app, Method::GET, &uri, Some(&bob), &[], None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, _) = send(&app, Method::DELETE, &uri, Some(&bob), &[], None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+async fn share_view_requires_confirmation_for_limited_clips(database_url: &str) {
+    let app = app(database_url).await;
+    let token = login(&app, "alice").await;
+
+    let public = create_clip(&app, &token, json!({ "content": "shared", "access_type": "unlisted" })).await;
+    let (status, _, body) = send(&app, Method::GET, &format!("/s/{}", public["short_url"].as_str().unwrap()), None, &[], None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["content"], "shared");
+    assert_eq!(body["data"]["view_count"], 1);
+
+    let burn = create_clip(&app, &token, json!({ "content": "secret", "access_type": "unlisted", "burn_after_read": true })).await;
+    let share_uri = format!("/s/{}", burn["short_url"].as_str().unwrap());
+
+    // 未确认时只返回预览，不计入查看次数
+    for _ in 0..2 {
+        let (status, headers, body) = send(&app, Method::GET, &share_uri, None, &[], None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CACHE_CONTROL], "no-store");
+        assert!(body["data"].get("content").is_none());
+        assert_eq!(body["data"]["remaining_views"], 1);
+    }
+
+    let (status, _, html) = send(&app, Method::GET, &share_uri, None, &[("accept", "text/html")], None).await;
+    assert_eq!((status, html), (StatusCode::OK, Value::Null));
+
+    let confirm_uri = format!("{}?confirm=1", share_uri);
+    let (status, _, body) = send(&app, Method::GET, &confirm_uri, None, &[], None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["content"], "secret");
+
+    let (status, _, _) = send(&app, Method::GET, &confirm_uri, None, &[], None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+async fn share_views_do_not_modify_clip(database_url: &str) {
+    let app = app(database_url).await;
+    let token = login(&app, "alice").await;
+
+    let clip = create_clip(&app, &token, json!({ "content": "shared", "access_type": "unlisted" })).await;
+    settle().await;
+    let (_, _, body) = send(&app, Method::GET, "/api/sync", Some(&token), &[], None).await;
+    let cursor = body["data"]["cursor"].as_str().unwrap().to_string();
+
+    // 查看只增加计数，updated_at 不变，Clip 也不会重新出现在增量同步中
+    let (status, _, body) = send(&app, Method::GET, &format!("/s/{}", clip["short_url"].as_str().unwrap()), None, &[], None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["view_count"], 1);
+
+    let (_, _, body) = send(&app, Method::GET, &format!("/api/clips/{}", clip["id"]), Some(&token), &[], None).await;
+    assert_eq!(body["data"]["view_count"], 1);
+    assert_eq!(body["data"]["updated_at"], clip["updated_at"]);
+
+    settle().await;
+    let (_, _, body) = send(&app, Method::GET, &format!("/api/sync?since={}", cursor), Some(&token), &[], None).await;
+    assert_eq!(body["data"]["clips"], json!([]));
+}
+
+async fn private_clips_are_not_shared(database_url: &str) {
+    let app = app(database_url).await;
+    let token = login(&app, "alice").await;
+
+    let clip = create_clip(&app, &token, json!({ "content": "private" })).await;
+    let share_uri = format!("/s/{}", clip["short_url"].as_str().unwrap());
+
+    let (status, _, _) = send(&app, Method::GET, &share_uri, None, &[], None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // 所有者登录后可以通过分享链接查看，且不计入查看次数
+    let (status, _, body) = send(&app, Method::GET, &share_uri, Some(&token), &[], None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["view_count"], 0);
+}